@@ -5,16 +5,17 @@ use num_bigint::Sign;
 use rustpython_common::borrow::BorrowValue;
 use widestring::{WideCString, WideChar};
 
-use crate::builtins::memory::try_buffer_from_object;
+use crate::builtins::memory::{try_buffer_from_object, Buffer, BufferOptions};
 use crate::builtins::{PyBytes, PyInt, PyStr, PyTypeRef};
-use crate::common::lock::PyRwLock;
+use crate::common::borrow::{BorrowedValue, BorrowedValueMut};
 use crate::function::FuncArgs;
 use crate::pyobject::{
     PyObjectRef, PyRef, PyResult, PyValue, StaticType, TryFromObject, TypeProtocol,
 };
 use crate::VirtualMachine;
 
-use crate::stdlib::ctypes::basics::{PyCData, RawBuffer};
+use crate::stdlib::ctypes::basics::{PyCData, PyCDataSequenceMethods};
+use crate::stdlib::ctypes::common::{format_string, size_of_type};
 use crate::stdlib::ctypes::pointer::PyCPointer;
 use crate::stdlib::ctypes::primitive::PySimpleType;
 
@@ -63,39 +64,53 @@ pub fn make_array_with_lenght(
     vm: &VirtualMachine,
 ) -> PyResult<PyRef<PyCArray>> {
     if let Ok(outer_type) = vm.get_attribute(cls.as_object().to_owned(), "_type_") {
-        match vm.get_attribute(outer_type, "_type_") {
-            Ok(inner_type)
-                if vm.issubclass(&inner_type.clone_class(), &PyCPointer::static_type())?
-                    || vm
-                        .issubclass(&inner_type.clone_class(), &PySimpleType::static_type())? =>
-            {
-                let subletter = vm
-                    .get_attribute(outer_type, "_type_")?
-                    .downcast_exact::<PyStr>(vm)
-                    .unwrap()
-                    .to_string();
-
-                let itemsize = get_size(subletter.as_str());
-
-                let myself = PyCArray {
-                    _type_: subletter,
-                    _length_: length,
-                }
-                .into_ref_with_type(vm, cls)?;
-
-                vm.set_attr(
-                    myself.as_object(),
-                    "_buffer",
-                    PyRwLock::new(RawBuffer {
-                        inner: Vec::with_capacity(length * itemsize).as_mut_ptr(),
-                        size: length * itemsize,
-                    }),
-                )?;
-
-                Ok(myself)
-            }
-            _ => Err(vm.new_type_error("_type_ must have storage info".to_string())),
+        // `outer_type` is the element ctype itself (e.g. `c_int`, `POINTER(c_int)`), so the
+        // storage-info check is made on that class itself, not on the *value* of its own `_type_`
+        // attribute (which for a simple type is a format-code string and is never a
+        // `PySimpleType`/`PyCPointer` subclass).
+        let outer_class = PyTypeRef::try_from_object(vm, outer_type.clone())
+            .map_err(|_| vm.new_type_error("_type_ must have storage info".to_string()))?;
+        let subletter = if vm.issubclass(&outer_class, &PyCPointer::static_type())? {
+            "P".to_string()
+        } else if vm.issubclass(&outer_class, &PySimpleType::static_type())? {
+            vm.get_attribute(outer_type, "_type_")?
+                .downcast_exact::<PyStr>(vm)
+                .unwrap()
+                .to_string()
+        } else {
+            return Err(vm.new_type_error("_type_ must have storage info".to_string()));
+        };
+
+        // `get_size` measures a bare `c_void` for the pointer codes; a pointer element is a full
+        // pointer wide.
+        let itemsize = match subletter.as_str() {
+            "P" | "z" | "Z" => size_of_type("P"),
+            other => get_size(other),
+        };
+
+        // The backing storage is a real, fully-initialized `PyCData` root (a safely
+        // owned `Vec<u8>` behind the shared borrow-flag model), not a dangling pointer
+        // into a `Vec` that gets dropped as soon as this function returns. Its
+        // `BufferOptions` are shaped like the array (not the generic single-byte view
+        // `PyCData::new` assumes), so exporting it through the buffer protocol reports
+        // the right `itemsize`/`format`/`shape` for `memoryview(my_array)`.
+        let options = BufferOptions {
+            len: length * itemsize,
+            itemsize,
+            readonly: false,
+            format: format_string(subletter.as_str()).into(),
+            shape: vec![length],
+            ..Default::default()
+        };
+        let buffer = PyCData::new_with_options(None, vec![0u8; length * itemsize], options)
+            .into_ref(vm);
+
+        PyCArray {
+            _type_: subletter,
+            _length_: length,
+            buffer,
         }
+        .into_ref_with_type(vm, cls)
     } else {
         Err(vm.new_attribute_error("class must define a '_type_' attribute".to_string()))
     }
@@ -103,8 +118,59 @@ pub fn make_array_with_lenght(
 
 #[pyclass(module = "_ctypes", name = "Array", base = "PyCData")]
 pub struct PyCArray {
-    _type_: String,
-    _length_: usize,
+    pub(crate) _type_: String,
+    pub(crate) _length_: usize,
+    // Aliases a `PyCData` root rather than owning a raw buffer directly, so borrows go through
+    // the same shared borrow-flag model every other `_CData` subtype uses.
+    pub(crate) buffer: PyRef<PyCData>,
+}
+
+/// Builds a `PyCArray` of `field_cls` that aliases `[offset, offset + size)` of `root` instead of
+/// allocating its own storage -- used for a nested `Array`-typed `Structure`/`Union` field, so
+/// writes through the child view land back in the parent's buffer.
+pub(crate) fn array_from_view(
+    field_cls: PyTypeRef,
+    root: PyRef<PyCData>,
+    offset: usize,
+    size: usize,
+    vm: &VirtualMachine,
+) -> PyResult<PyObjectRef> {
+    let length = usize::try_from_object(vm, vm.get_attribute(field_cls.as_object().to_owned(), "_length_")?)?;
+    let outer_type = vm.get_attribute(field_cls.as_object().to_owned(), "_type_")?;
+    let outer_class = PyTypeRef::try_from_object(vm, outer_type.clone())
+        .map_err(|_| vm.new_type_error("_type_ must have storage info".to_string()))?;
+    let subletter = if vm.issubclass(&outer_class, &PyCPointer::static_type())? {
+        "P".to_string()
+    } else if vm.issubclass(&outer_class, &PySimpleType::static_type())? {
+        vm.get_attribute(outer_type, "_type_")?
+            .downcast_exact::<PyStr>(vm)
+            .unwrap()
+            .to_string()
+    } else {
+        return Err(vm.new_type_error("_type_ must have storage info".to_string()));
+    };
+    let itemsize = match subletter.as_str() {
+        "P" | "z" | "Z" => size_of_type("P"),
+        other => get_size(other),
+    };
+
+    let options = BufferOptions {
+        len: size,
+        itemsize,
+        readonly: false,
+        format: format_string(subletter.as_str()).into(),
+        shape: vec![length],
+        ..Default::default()
+    };
+    let view = PyCData::new_view(root, offset, size, options).into_ref(vm);
+
+    Ok(PyCArray {
+        _type_: subletter,
+        _length_: length,
+        buffer: view,
+    }
+    .into_ref_with_type(vm, field_cls)?
+    .into_object())
 }
 
 impl fmt::Debug for PyCArray {
@@ -124,7 +190,31 @@ impl PyValue for PyCArray {
     }
 }
 
-#[pyimpl(flags(BASETYPE))]
+impl PyCDataSequenceMethods for PyCArray {}
+
+// Delegates straight to the backing `PyCData` root, whose `BufferOptions` were already shaped
+// for this array (itemsize/format/shape) at construction time in `make_array_with_lenght`; the
+// borrow-flag aliasing rules (a live export blocking conflicting mutation) come along for free
+// since `self.buffer` carries them.
+impl Buffer for PyCArray {
+    fn obj_bytes(&self) -> BorrowedValue<[u8]> {
+        self.buffer.obj_bytes()
+    }
+
+    fn obj_bytes_mut(&self) -> BorrowedValueMut<[u8]> {
+        self.buffer.obj_bytes_mut()
+    }
+
+    fn release(&self) {
+        self.buffer.release()
+    }
+
+    fn get_options(&self) -> BorrowedValue<BufferOptions> {
+        self.buffer.get_options()
+    }
+}
+
+#[pyimpl(with(PyCDataSequenceMethods, Buffer), flags(BASETYPE))]
 impl PyCArray {
     #[pyslot]
     fn tp_new(cls: PyTypeRef, vm: &VirtualMachine) -> PyResult<PyRef<Self>> {
@@ -162,8 +252,7 @@ impl PyCArray {
 
     #[pyproperty(name = "value")]
     pub fn value(&self, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
-        let obj = self.into_object(vm);
-        let buffer = try_buffer_from_object(vm, &obj)?;
+        let buffer = self.buffer.try_borrow(vm)?;
 
         let res = if self._type_ == "u" {
             vm.new_pyobj(
@@ -171,7 +260,6 @@ impl PyCArray {
                     if cfg!(windows) {
                         WideCString::from_vec_with_nul_unchecked(
                             buffer
-                                .obj_bytes()
                                 .chunks_exact(2)
                                 .map(|c| {
                                     let chunk: [u8; 2] = c.try_into().unwrap();
@@ -182,7 +270,6 @@ impl PyCArray {
                     } else {
                         WideCString::from_vec_with_nul_unchecked(
                             buffer
-                                .obj_bytes()
                                 .chunks(4)
                                 .map(|c| {
                                     let chunk: [u8; 4] = c.try_into().unwrap();
@@ -197,13 +284,11 @@ impl PyCArray {
             )
         } else {
             // self._type_ == "c"
-            let bytes = buffer.obj_bytes();
-
-            let bytes_inner = if let Some((last, elements)) = bytes.split_last() {
+            let bytes_inner = if let Some((last, elements)) = buffer.split_last() {
                 if *last == 0 {
                     elements.to_vec()
                 } else {
-                    bytes.to_vec()
+                    buffer.to_vec()
                 }
             } else {
                 vec![0; 0]
@@ -217,10 +302,8 @@ impl PyCArray {
 
     #[pyproperty(name = "value", setter)]
     fn set_value(&self, value: PyObjectRef, vm: &VirtualMachine) -> PyResult<()> {
-        let obj = self.into_object(vm);
-        let buffer = try_buffer_from_object(vm, &obj)?;
-        let my_size = buffer.get_options().len;
-        let mut bytes = buffer.obj_bytes_mut();
+        let mut bytes = self.buffer.try_borrow_mut(vm)?;
+        let my_size = bytes.len();
 
         if self._type_ == "c" {
             // bytes
@@ -234,6 +317,12 @@ impl PyCArray {
                     if wide_bytes.len() < my_size {
                         bytes[my_size] = 0;
                     }
+                    // `value` was only copied byte-for-byte above, not aliased, so nothing here
+                    // needs to outlive this call beyond what the copy already captured. Still,
+                    // re-registering on every assignment keeps this array's `_objects` entry in
+                    // sync with whatever was most recently written, mirroring CPython's
+                    // `b_objects` bookkeeping for byte-array-backed fields.
+                    self.buffer.keep_alive(0, value.as_object().clone());
                     Ok(())
                 }
             } else {
@@ -283,18 +372,14 @@ impl PyCArray {
     #[pyproperty(name = "raw")]
     pub fn raw(&self, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
         // self._type_ == "c"
-
-        let obj = self.into_object(vm);
-        let buffer = try_buffer_from_object(vm, &obj)?;
-
-        Ok(PyBytes::from(buffer.obj_bytes().to_vec()).into_object(vm))
+        let buffer = self.buffer.try_borrow(vm)?;
+        Ok(PyBytes::from(buffer.to_vec()).into_object(vm))
     }
 
     #[pyproperty(name = "raw", setter)]
     fn set_raw(&self, value: PyObjectRef, vm: &VirtualMachine) -> PyResult<()> {
-        let obj = self.into_object(vm);
-        let my_buffer = try_buffer_from_object(vm, &obj)?;
-        let my_size = my_buffer.get_options().len;
+        let mut my_buffer = self.buffer.try_borrow_mut(vm)?;
+        let my_size = my_buffer.len();
 
         let new_value = try_buffer_from_object(vm, &value)?;
         let new_size = new_value.get_options().len;
@@ -303,10 +388,30 @@ impl PyCArray {
         if new_size > my_size {
             Err(vm.new_value_error("byte string too long".to_string()))
         } else {
-            let mut borrowed_buffer = my_buffer.obj_bytes_mut();
             let src = new_value.obj_bytes();
-            borrowed_buffer[0..new_size].copy_from_slice(&src);
+            my_buffer[0..new_size].copy_from_slice(&src);
+            drop(src);
+            // As with `set_value`, the bytes are copied rather than aliased, but registering the
+            // source object here keeps this array's `_objects` entry current with whatever was
+            // last assigned, matching CPython's bookkeeping for `.raw =` assignment.
+            self.buffer.keep_alive(0, value);
             Ok(())
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::get_size;
+    use std::mem;
+
+    #[test]
+    fn get_size_matches_native_widths() {
+        assert_eq!(get_size("b"), mem::size_of::<std::os::raw::c_schar>());
+        assert_eq!(get_size("i"), mem::size_of::<std::os::raw::c_int>());
+        assert_eq!(get_size("q"), mem::size_of::<std::os::raw::c_longlong>());
+        assert_eq!(get_size("d"), mem::size_of::<std::os::raw::c_double>());
+        assert_eq!(get_size("P"), mem::size_of::<std::os::raw::c_void>());
+        assert_eq!(get_size("Z"), get_size("P"));
+    }
+}
@@ -1,18 +1,23 @@
+use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicIsize, Ordering};
+use std::sync::Arc;
 use std::{fmt, os::raw::c_void, slice};
 
-use crate::builtins::bytearray::PyByteArray;
 use crate::builtins::int::PyInt;
-use crate::builtins::memory::{Buffer, BufferOptions};
-use crate::builtins::pystr::PyStrRef;
+use crate::builtins::memory::{try_buffer_from_object, Buffer, BufferOptions};
+use crate::builtins::pystr::{PyStr, PyStrRef};
 use crate::builtins::pytype::PyTypeRef;
 use crate::common::borrow::{BorrowedValue, BorrowedValueMut};
+use crate::common::lock::PyRwLock;
 use crate::function::OptionalArg;
 use crate::pyobject::{
-    PyObjectRc, PyObjectRef, PyRef, PyResult, PyValue, StaticType, TryFromObject,
+    PyObjectRc, PyObjectRef, PyRef, PyResult, PyValue, StaticType, TryFromObject, TypeProtocol,
 };
 use crate::VirtualMachine;
 
-use crossbeam_utils::atomic::AtomicCell;
+use crate::stdlib::ctypes::array::PyCArray;
+use crate::stdlib::ctypes::structure::{compute_layout, type_info, PyCUnion};
 
 // GenericPyCData_new -> PyResult<PyObjectRef>
 pub fn generic_pycdata_new(type_: PyTypeRef, vm: &VirtualMachine) {
@@ -52,6 +57,34 @@ fn at_address(cls: &PyTypeRef, buf: usize, vm: &VirtualMachine) -> PyResult<Vec<
     }
 }
 
+/// `offset` as it's passed to `from_buffer`/`from_buffer_copy`: defaults to 0, and must not be
+/// negative.
+fn resolve_offset(offset: OptionalArg<isize>, vm: &VirtualMachine) -> PyResult<usize> {
+    let offset = offset.unwrap_or(0);
+    if offset < 0 {
+        return Err(vm.new_value_error("offset cannot be negative".to_string()));
+    }
+    Ok(offset as usize)
+}
+
+/// `sizeof(cls)` for a ctype class: a scalar's `_type_` code, an array's `_type_`/`_length_`
+/// pair, a `POINTER` class, or a `Structure`/`Union`'s computed layout. `_size_` is never set by
+/// any ctype class in this tree (`compute_layout` is what derives a size, not a class attribute
+/// read off it), so `Structure`/`Union`'s size has to be recomputed from `_fields_` here the same
+/// way `StructureData::new` does; `type_info` already covers scalar/array/pointer classes.
+fn cdata_size(cls: &PyTypeRef, vm: &VirtualMachine) -> PyResult<usize> {
+    let obj = cls.as_object().to_owned();
+
+    if vm.get_attribute(obj.clone(), "_fields_").is_ok() {
+        let is_union = vm.issubclass(cls, &PyCUnion::static_type())?;
+        let (_fields, size, _align) = compute_layout(cls, is_union, vm)?;
+        return Ok(size);
+    }
+
+    let (size, _align) = type_info(&obj, vm)?;
+    Ok(size)
+}
+
 #[pyimpl]
 pub trait PyCDataMethods: PyValue {
     // A lot of the logic goes in this trait
@@ -75,7 +108,7 @@ pub trait PyCDataMethods: PyValue {
     ) -> PyResult<PyCData> {
         if let Ok(obj) = address.downcast_exact::<PyInt>(vm) {
             if let Ok(v) = usize::try_from_object(vm, obj.into_object()) {
-                let buffer = PyByteArray::from(at_address(&cls, v, vm)?);
+                let buffer = at_address(&cls, v, vm)?;
                 Ok(PyCData::new(None, Some(buffer)))
             } else {
                 Err(vm.new_runtime_error("casting pointer failed".to_string()))
@@ -89,17 +122,62 @@ pub trait PyCDataMethods: PyValue {
     fn from_buffer(
         cls: PyTypeRef,
         obj: PyObjectRef,
-        offset: OptionalArg,
+        offset: OptionalArg<isize>,
         vm: &VirtualMachine,
-    ) -> PyResult<PyCData>;
+    ) -> PyResult<PyCData> {
+        let offset = resolve_offset(offset, vm)?;
+        let size = cdata_size(&cls, vm)?;
+
+        let buffer = try_buffer_from_object(vm, &obj)?;
+        let (readonly, buf_len) = {
+            let options = buffer.get_options();
+            (options.readonly, options.len)
+        };
+        if readonly {
+            return Err(vm.new_type_error("underlying buffer is not writable".to_string()));
+        }
+        if offset
+            .checked_add(size)
+            .map_or(true, |needed| needed > buf_len)
+        {
+            return Err(vm.new_value_error("Buffer size too small".to_string()));
+        }
+
+        // SAFETY: `offset + size <= buf_len`. `new_external` registers `obj` in `_objects` --
+        // see the keep-alive model note on `PyCData` below.
+        let ptr = unsafe { buffer.obj_bytes_mut().as_mut_ptr().add(offset) };
+        let options = BufferOptions {
+            len: size,
+            itemsize: 1,
+            readonly: false,
+            format: "B".into(),
+            ..Default::default()
+        };
+        Ok(PyCData::new_external(obj, ptr, size, options))
+    }
 
     #[pyclassmethod]
     fn from_buffer_copy(
         cls: PyTypeRef,
         obj: PyObjectRef,
-        offset: OptionalArg,
+        offset: OptionalArg<isize>,
         vm: &VirtualMachine,
-    ) -> PyResult<PyCData>;
+    ) -> PyResult<PyCData> {
+        let offset = resolve_offset(offset, vm)?;
+        let size = cdata_size(&cls, vm)?;
+
+        let buffer = try_buffer_from_object(vm, &obj)?;
+        let buf_len = buffer.get_options().len;
+        if offset
+            .checked_add(size)
+            .map_or(true, |needed| needed > buf_len)
+        {
+            return Err(vm.new_value_error("Buffer size too small".to_string()));
+        }
+
+        let bytes = buffer.obj_bytes()[offset..offset + size].to_vec();
+        Ok(PyCData::new(None, Some(bytes)))
+    }
 
     #[pyclassmethod]
     fn in_dll(
@@ -110,20 +188,70 @@ pub trait PyCDataMethods: PyValue {
     ) -> PyResult<PyCData>;
 }
 
+lazy_static::lazy_static! {
+    // Keyed by (ctype's identity pointer, length) so `c_int * 3` evaluated twice returns the
+    // *same* Array subtype object, matching real ctypes' identity guarantee
+    // (`(c_int * 3) is (c_int * 3)`).
+    static ref ARRAY_TYPE_CACHE: PyRwLock<HashMap<(usize, usize), PyTypeRef>> =
+        PyRwLock::new(HashMap::new());
+}
+
+/// `ctype * n`: builds (or returns the cached) `Array` subtype whose `_type_` is `ctype` and
+/// `_length_` is `n` -- the core of how ctypes code spells a fixed-size array type. Repeating
+/// this on the result (`c_int * 3 * 2`) nests naturally, since the Array subtype this returns is
+/// itself a valid `_type_` to repeat again.
+pub fn sq_repeat(ctype: PyTypeRef, n: isize, vm: &VirtualMachine) -> PyResult<PyTypeRef> {
+    if n < 0 {
+        return Err(vm.new_value_error("Array length must be >= 0".to_string()));
+    }
+    let n = n as usize;
+    let key = (ctype.as_object() as *const _ as usize, n);
+
+    if let Some(cached) = ARRAY_TYPE_CACHE.read().get(&key) {
+        return Ok(cached.clone());
+    }
+
+    let elem_name = vm
+        .get_attribute(ctype.as_object().to_owned(), "__name__")
+        .ok()
+        .and_then(|o| o.downcast_exact::<PyStr>(vm).ok())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| "_unknown_".to_string());
+
+    let array_type = vm.ctx.new_class(
+        Box::leak(format!("{}_Array_{}", elem_name, n).into_boxed_str()),
+        PyCArray::static_type(),
+        Default::default(),
+    );
+    vm.set_attr(array_type.as_object(), "_type_", ctype.into_object())?;
+    vm.set_attr(array_type.as_object(), "_length_", vm.new_pyobj(n))?;
+
+    ARRAY_TYPE_CACHE.write().insert(key, array_type.clone());
+
+    Ok(array_type)
+}
+
 #[pyimpl]
 pub trait PyCDataSequenceMethods: PyValue {
     // CDataType_as_sequence methods are default for all *Type_Type
     // Basically the sq_repeat slot is CDataType_repeat
     // which transforms into a Array
 
-    // #[pymethod(name = "__mul__")]
-    // fn mul(&self, counter: isize, vm: &VirtualMachine) -> PyObjectRef {
-    // }
+    // Real ctypes hangs `sq_repeat` off each `*Type_Type` metaclass, so `ctype * n` dispatches
+    // without `ctype` itself needing a `__mul__` attribute. This tree has no such per-ctype
+    // metaclass yet (every ctypes class here is a plain `type` instance), so `ctype * n` can't
+    // be wired as a genuine operator slot; `mul`/`rmul` are exposed as ordinary classmethods
+    // instead, callable as `ctype.__mul__(n)`, with `sq_repeat` doing the real work so the
+    // operator slot is a one-line addition once that metaclass layer exists.
+    #[pyclassmethod(name = "__mul__")]
+    fn mul(cls: PyTypeRef, n: isize, vm: &VirtualMachine) -> PyResult<PyTypeRef> {
+        sq_repeat(cls, n, vm)
+    }
 
-    // #[pymethod(name = "__rmul__")]
-    // fn rmul(&self, counter: isize, vm: &VirtualMachine) -> PyObjectRef {
-    //     self.mul(counter, vm)
-    // }
+    #[pyclassmethod(name = "__rmul__")]
+    fn rmul(cls: PyTypeRef, n: isize, vm: &VirtualMachine) -> PyResult<PyTypeRef> {
+        sq_repeat(cls, n, vm)
+    }
 }
 
 // This trait will be used by all types
@@ -137,18 +265,158 @@ pub trait PyCDataBuffer: Buffer {
     fn get_options(&self) -> BorrowedValue<BufferOptions>;
 }
 
+// 0 means free, a positive count means N live shared (read) borrows, and WRITE is the
+// sentinel for a single live exclusive (write) borrow. There is no blocking here: a borrow
+// that can't be taken immediately is a Python-level error (BufferError/ValueError), not a
+// thread park, since the "lock" models Python-level aliasing rules, not thread exclusion.
+const WRITE: isize = -1;
+
+#[derive(Debug)]
+struct BorrowFlag(AtomicIsize);
+
+impl BorrowFlag {
+    fn new() -> Self {
+        BorrowFlag(AtomicIsize::new(0))
+    }
+
+    fn try_borrow(&self) -> Result<(), ()> {
+        loop {
+            let cur = self.0.load(Ordering::Acquire);
+            if cur == WRITE {
+                return Err(());
+            }
+            if self
+                .0
+                .compare_exchange(cur, cur + 1, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    fn try_borrow_mut(&self) -> Result<(), ()> {
+        self.0
+            .compare_exchange(0, WRITE, Ordering::AcqRel, Ordering::Acquire)
+            .map(drop)
+            .map_err(drop)
+    }
+
+    fn release_borrow(&self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    fn release_borrow_mut(&self) {
+        self.0.store(0, Ordering::Release);
+    }
+}
+
+/// Where a `_CData` instance's bytes actually live. A `Root` owns the storage; any other
+/// `_CData` built from it (a struct field, an array element, a pointer target) is a `Sub` that
+/// aliases a `[offset, offset + length)` window of the root's buffer instead of copying it, and
+/// shares the root's `BorrowFlag` so that borrowing a sub-object borrows the whole aliased
+/// region. `PyCData::new_view` is what constructs a `Sub`; its real callers are a nested
+/// `Structure`/`Union`-typed field (`structure.rs`'s `$name::from_view`) and a nested `Array`
+/// field (`array.rs`'s `array_from_view`), both reached through `StructureData::getattr`.
+enum PyCDataStorage {
+    Root(UnsafeCell<Vec<u8>>),
+    Sub {
+        root: PyRef<PyCData>,
+        offset: usize,
+        length: usize,
+    },
+    // `from_buffer`: aliases a buffer-protocol object's memory directly instead of a `PyCData`
+    // root, so the keep-alive reference lives in `_objects` rather than a `Sub`'s `root` field.
+    External {
+        ptr: *mut u8,
+        length: usize,
+    },
+}
+
+// SAFETY: all access to the raw pointer/`UnsafeCell` goes through `try_borrow`/`try_borrow_mut`,
+// which arbitrate access via `borrow_flag` the same way a `RwLock` would.
+unsafe impl Sync for PyCDataStorage {}
+unsafe impl Send for PyCDataStorage {}
+
+/// A live shared (read) borrow of a `_CData` buffer. Dereferences to `&[u8]`; releases the
+/// borrow on drop.
+pub struct PyCDataReadGuard<'a> {
+    bytes: &'a [u8],
+    flag: Arc<BorrowFlag>,
+}
+
+impl<'a> std::ops::Deref for PyCDataReadGuard<'a> {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        self.bytes
+    }
+}
+
+impl<'a> Drop for PyCDataReadGuard<'a> {
+    fn drop(&mut self) {
+        self.flag.release_borrow();
+    }
+}
+
+/// A live exclusive (write) borrow of a `_CData` buffer. Dereferences to `&mut [u8]`; releases
+/// the borrow on drop.
+pub struct PyCDataWriteGuard<'a> {
+    bytes: &'a mut [u8],
+    flag: Arc<BorrowFlag>,
+}
+
+impl<'a> std::ops::Deref for PyCDataWriteGuard<'a> {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        self.bytes
+    }
+}
+
+impl<'a> std::ops::DerefMut for PyCDataWriteGuard<'a> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.bytes
+    }
+}
+
+impl<'a> Drop for PyCDataWriteGuard<'a> {
+    fn drop(&mut self) {
+        self.flag.release_borrow_mut();
+    }
+}
+
+fn buffer_error(vm: &VirtualMachine, msg: impl Into<String>) -> crate::pyobject::PyBaseExceptionRef {
+    vm.new_exception_msg(vm.ctx.exceptions.buffer_error.clone(), msg.into())
+}
+
 // This Trait is the equivalent of PyCData_Type on tp_base for
 // Struct_Type, Union_Type, PyCPointer_Type
 // PyCArray_Type, PyCSimple_Type, PyCFuncPtr_Type
+//
+// # The keep-alive model
+//
+// Rust's borrow checker doesn't see the aliasing a `POINTER`, `c_char_p`, or `from_buffer`
+// view sets up with some other Python object's storage -- from its point of view `usize`
+// addresses and raw pointers don't borrow anything. `_objects` is what keeps that aliased
+// object alive for as long as something here still points into it, mirroring CPython's
+// `b_objects` dict: keyed by the byte offset of the pointer/reference that needs the object
+// kept alive (not just a flat list), so re-assigning the same field replaces its old entry
+// instead of accumulating one per assignment forever. `keep_alive` registers a single entry;
+// `merge_objects` folds a nested aggregate field's whole table into its parent's at once, so a
+// pointer buried inside a struct/array field stays alive through the outermost owner too. Every
+// other "kept alive for as long as X" comment in this module is this same mechanism applied to
+// a value that isn't itself a `PyCData` (a pointer's `target`, a closure's boxed user data): see
+// here for why it exists.
 #[pyclass(module = "ctypes", name = "_CData")]
 pub struct PyCData {
-    _objects: AtomicCell<Vec<PyObjectRc>>,
-    _buffer: AtomicCell<PyByteArray>,
+    _objects: PyRwLock<HashMap<usize, PyObjectRc>>,
+    storage: PyCDataStorage,
+    borrow_flag: Arc<BorrowFlag>,
+    options: BufferOptions,
 }
 
 impl fmt::Debug for PyCData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "PyCData {{ _objects: {{}}, _buffer: {{}}}}",)
+        write!(f, "PyCData {{ _objects: {{}}, storage: {{}}}}",)
     }
 }
 
@@ -159,15 +427,228 @@ impl PyValue for PyCData {
 }
 
 impl PyCData {
-    fn new(objs: Option<Vec<PyObjectRc>>, buffer: Option<PyByteArray>) -> Self {
+    pub(crate) fn new(objs: Option<HashMap<usize, PyObjectRc>>, buffer: Option<Vec<u8>>) -> Self {
+        let buffer = buffer.unwrap_or_default();
+        let options = BufferOptions {
+            len: buffer.len(),
+            itemsize: 1,
+            readonly: false,
+            format: "B".into(),
+            ..Default::default()
+        };
+        PyCData {
+            _objects: PyRwLock::new(objs.unwrap_or_default()),
+            storage: PyCDataStorage::Root(UnsafeCell::new(buffer)),
+            borrow_flag: Arc::new(BorrowFlag::new()),
+            options,
+        }
+    }
+
+    /// Builds a sub-object that aliases `[offset, offset + length)` of `root`'s buffer (a
+    /// struct field, an array element, ...) instead of copying it. Borrowing the sub-object
+    /// borrows the same aliased region of `root`, because they share `root`'s `borrow_flag`.
+    pub fn new_view(root: PyRef<PyCData>, offset: usize, length: usize, options: BufferOptions) -> Self {
+        let borrow_flag = Arc::clone(&root.borrow_flag);
+        PyCData {
+            _objects: PyRwLock::new(HashMap::new()),
+            storage: PyCDataStorage::Sub {
+                root,
+                offset,
+                length,
+            },
+            borrow_flag,
+            options,
+        }
+    }
+
+    /// Builds a root object whose buffer export reports the `_type_`/`_length_`-derived shape
+    /// and `struct`-format code, instead of the generic single-byte view `new` assumes. Used by
+    /// `Array`/`Structure` constructors that know their itemsize and element type up front.
+    pub fn new_with_options(
+        objs: Option<HashMap<usize, PyObjectRc>>,
+        buffer: Vec<u8>,
+        options: BufferOptions,
+    ) -> Self {
+        PyCData {
+            _objects: PyRwLock::new(objs.unwrap_or_default()),
+            storage: PyCDataStorage::Root(UnsafeCell::new(buffer)),
+            borrow_flag: Arc::new(BorrowFlag::new()),
+            options,
+        }
+    }
+
+    /// Builds a root object that aliases `obj`'s buffer-protocol memory at `ptr` (already
+    /// offset, `length` bytes long) instead of owning its own storage -- the `from_buffer` path.
+    /// Registers `obj` in `_objects` (see the keep-alive model note on `PyCData` above), since
+    /// `ptr` only remains valid while `obj`'s backing storage does.
+    pub(crate) fn new_external(obj: PyObjectRef, ptr: *mut u8, length: usize, options: BufferOptions) -> Self {
+        let mut objects = HashMap::new();
+        objects.insert(0, obj);
         PyCData {
-            _objects: AtomicCell::new(objs.unwrap_or(Vec::new())),
-            _buffer: AtomicCell::new(buffer.unwrap_or(PyByteArray::from(Vec::new()))),
+            _objects: PyRwLock::new(objects),
+            storage: PyCDataStorage::External { ptr, length },
+            borrow_flag: Arc::new(BorrowFlag::new()),
+            options,
+        }
+    }
+
+    /// Like `new_external`, but for memory with no buffer-protocol object backing it at all --
+    /// a raw foreign address (`POINTER(...).contents`, a `CFuncPtr` restype). Nothing to
+    /// register in `_objects`: the caller (whoever holds the address) is already solely
+    /// responsible for the memory staying valid.
+    pub(crate) fn new_external_unowned(ptr: *mut u8, length: usize, options: BufferOptions) -> Self {
+        PyCData {
+            _objects: PyRwLock::new(HashMap::new()),
+            storage: PyCDataStorage::External { ptr, length },
+            borrow_flag: Arc::new(BorrowFlag::new()),
+            options,
+        }
+    }
+
+    /// Registers `obj` in `_objects` at `offset` -- e.g. a `c_char_p`/`c_wchar_p` or a `POINTER`
+    /// field was just set to it. See the keep-alive model note on `PyCData` above.
+    pub fn keep_alive(&self, offset: usize, obj: PyObjectRef) {
+        self._objects.write().insert(offset, obj);
+    }
+
+    /// Folds `child`'s `_objects` into this one, shifting each of the child's offsets by
+    /// `base_offset` (the child's own position within this object). See the keep-alive model
+    /// note on `PyCData` above.
+    pub fn merge_objects(&self, base_offset: usize, child: &PyCData) {
+        let mut objects = self._objects.write();
+        for (offset, obj) in child._objects.read().iter() {
+            objects.insert(base_offset + offset, obj.clone());
+        }
+    }
+
+    /// Walks the `Sub` chain up to the instance that actually owns the buffer.
+    fn root(&self) -> &PyCData {
+        match &self.storage {
+            PyCDataStorage::Root(_) | PyCDataStorage::External { .. } => self,
+            PyCDataStorage::Sub { root, .. } => root.root(),
+        }
+    }
+
+    /// The `[offset, offset + length)` window this instance covers, with `offset` accumulated
+    /// all the way up to `root()`'s buffer -- not just the offset within its immediate parent.
+    /// A `Sub` nested two or more levels deep (a struct field inside a struct field, or
+    /// `PyCPointer.contents` on a pointer whose pointee itself has a nested field) would
+    /// otherwise have its parent's own offset silently dropped.
+    fn window(&self) -> (usize, usize) {
+        match &self.storage {
+            PyCDataStorage::Root(buf) => (0, unsafe { &*buf.get() }.len()),
+            PyCDataStorage::External { length, .. } => (0, *length),
+            PyCDataStorage::Sub { root, offset, length } => {
+                let (root_offset, _) = root.window();
+                (root_offset + offset, *length)
+            }
+        }
+    }
+
+    pub fn try_borrow(&self, vm: &VirtualMachine) -> PyResult<PyCDataReadGuard<'_>> {
+        self.borrow_flag
+            .try_borrow()
+            .map_err(|_| buffer_error(vm, "ctypes object is currently locked for writing"))?;
+
+        let (offset, length) = self.window();
+        let root = self.root();
+        let bytes = match &root.storage {
+            PyCDataStorage::Root(buf) => unsafe { &(*buf.get())[offset..offset + length] },
+            PyCDataStorage::External { ptr, length: root_len } => unsafe {
+                &slice::from_raw_parts(*ptr, *root_len)[offset..offset + length]
+            },
+            PyCDataStorage::Sub { .. } => unreachable!("root() never returns a Sub"),
+        };
+
+        Ok(PyCDataReadGuard {
+            bytes,
+            flag: Arc::clone(&self.borrow_flag),
+        })
+    }
+
+    pub fn try_borrow_mut(&self, vm: &VirtualMachine) -> PyResult<PyCDataWriteGuard<'_>> {
+        self.borrow_flag
+            .try_borrow_mut()
+            .map_err(|_| buffer_error(vm, "ctypes object is currently locked"))?;
+
+        let (offset, length) = self.window();
+        let root = self.root();
+        let bytes = match &root.storage {
+            PyCDataStorage::Root(buf) => unsafe { &mut (*buf.get())[offset..offset + length] },
+            PyCDataStorage::External { ptr, length: root_len } => unsafe {
+                &mut slice::from_raw_parts_mut(*ptr, *root_len)[offset..offset + length]
+            },
+            PyCDataStorage::Sub { .. } => unreachable!("root() never returns a Sub"),
+        };
+
+        Ok(PyCDataWriteGuard {
+            bytes,
+            flag: Arc::clone(&self.borrow_flag),
+        })
+    }
+
+    /// Infallible counterpart of `try_borrow`, for the generic buffer-protocol entry points
+    /// (`Buffer::obj_bytes`) that have no `VirtualMachine` to raise a `BufferError` through.
+    /// Panics instead, the same way `RefCell::borrow` does on a conflicting borrow.
+    fn borrow_bytes(&self) -> PyCDataReadGuard<'_> {
+        self.borrow_flag
+            .try_borrow()
+            .expect("ctypes buffer is exclusively borrowed elsewhere");
+
+        let (offset, length) = self.window();
+        let bytes = match &self.root().storage {
+            PyCDataStorage::Root(buf) => unsafe { &(*buf.get())[offset..offset + length] },
+            PyCDataStorage::External { ptr, length: root_len } => unsafe {
+                &slice::from_raw_parts(*ptr, *root_len)[offset..offset + length]
+            },
+            PyCDataStorage::Sub { .. } => unreachable!("root() never returns a Sub"),
+        };
+
+        PyCDataReadGuard {
+            bytes,
+            flag: Arc::clone(&self.borrow_flag),
+        }
+    }
+
+    /// Infallible counterpart of `try_borrow_mut`; see `borrow_bytes`.
+    fn borrow_bytes_mut(&self) -> PyCDataWriteGuard<'_> {
+        self.borrow_flag
+            .try_borrow_mut()
+            .expect("ctypes buffer is already borrowed elsewhere");
+
+        let (offset, length) = self.window();
+        let bytes = match &self.root().storage {
+            PyCDataStorage::Root(buf) => unsafe { &mut (*buf.get())[offset..offset + length] },
+            PyCDataStorage::External { ptr, length: root_len } => unsafe {
+                &mut slice::from_raw_parts_mut(*ptr, *root_len)[offset..offset + length]
+            },
+            PyCDataStorage::Sub { .. } => unreachable!("root() never returns a Sub"),
+        };
+
+        PyCDataWriteGuard {
+            bytes,
+            flag: Arc::clone(&self.borrow_flag),
         }
     }
 }
 
-#[pyimpl]
+impl Buffer for PyCData {
+    fn obj_bytes(&self) -> BorrowedValue<[u8]> {
+        BorrowedValue::map(self.borrow_bytes(), |guard| &**guard)
+    }
+
+    fn obj_bytes_mut(&self) -> BorrowedValueMut<[u8]> {
+        BorrowedValueMut::map(self.borrow_bytes_mut(), |guard| &mut **guard)
+    }
+
+    fn release(&self) {}
+
+    fn get_options(&self) -> BorrowedValue<BufferOptions> {
+        (&self.options).into()
+    }
+}
+
+#[pyimpl(with(Buffer))]
 impl PyCData {
     // PyCData_methods
     #[pymethod(name = "__ctypes_from_outparam__")]
@@ -184,3 +665,36 @@ impl PyCData {
 // impl PyCDataBuffer for PyCData {
 
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::BorrowFlag;
+
+    #[test]
+    fn shared_borrows_stack() {
+        let flag = BorrowFlag::new();
+        assert!(flag.try_borrow().is_ok());
+        assert!(flag.try_borrow().is_ok());
+        flag.release_borrow();
+        flag.release_borrow();
+    }
+
+    #[test]
+    fn exclusive_borrow_excludes_shared() {
+        let flag = BorrowFlag::new();
+        assert!(flag.try_borrow().is_ok());
+        assert!(flag.try_borrow_mut().is_err());
+        flag.release_borrow();
+        assert!(flag.try_borrow_mut().is_ok());
+    }
+
+    #[test]
+    fn exclusive_borrow_excludes_exclusive() {
+        let flag = BorrowFlag::new();
+        assert!(flag.try_borrow_mut().is_ok());
+        assert!(flag.try_borrow_mut().is_err());
+        assert!(flag.try_borrow().is_err());
+        flag.release_borrow_mut();
+        assert!(flag.try_borrow().is_ok());
+    }
+}
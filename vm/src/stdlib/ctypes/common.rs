@@ -9,7 +9,7 @@ use libloading::Library;
 
 use crate::builtins::PyTypeRef;
 use crate::common::lock::PyRwLock;
-use crate::pyobject::{PyValue, StaticType, PyRef, PyObjectRef};
+use crate::pyobject::{PyObjectRef, PyRef, PyResult, PyValue, StaticType, TryFromObject};
 use crate::VirtualMachine;
 
 pub const SIMPLE_TYPE_CHARS: &str = "cbBhHiIlLdfuzZqQP?g";
@@ -36,6 +36,121 @@ pub fn convert_type(ty: &str) -> middle::Type {
     }
 }
 
+/// Native `sizeof`/`alignof` for a simple ctypes type code. On every platform this crate
+/// targets, alignment of these scalar C types equals their size, so a single table serves both.
+pub fn size_of_type(ty: &str) -> usize {
+    use std::mem::size_of;
+    use std::os::raw::*;
+
+    match ty {
+        "c" | "b" => size_of::<c_schar>(),
+        "B" | "?" => size_of::<c_uchar>(),
+        "h" => size_of::<c_short>(),
+        "H" => size_of::<c_ushort>(),
+        "i" | "u" => size_of::<c_int>(),
+        "I" => size_of::<c_uint>(),
+        "l" => size_of::<c_long>(),
+        "L" => size_of::<c_ulong>(),
+        "q" => size_of::<c_longlong>(),
+        "Q" => size_of::<c_ulonglong>(),
+        "f" => size_of::<c_float>(),
+        "d" | "g" => size_of::<c_double>(),
+        "z" | "Z" | "P" | _ => size_of::<*const c_void>(),
+    }
+}
+
+pub fn align_of_type(ty: &str) -> usize {
+    // Every type table entry above is a scalar with alignment == size on the platforms this
+    // crate targets (no over-aligned long doubles are modeled).
+    size_of_type(ty)
+}
+
+/// The `struct`-module format character for a ctypes type code (`_type_`). Most of
+/// `SIMPLE_TYPE_CHARS` already are struct format characters; the rest are remapped: `z`/`Z`
+/// (char/wchar pointers) to the generic pointer code `P`, `g` to `d`, and `u` to `i`.
+pub fn format_string(ty: &str) -> &'static str {
+    match ty {
+        "z" | "Z" => "P",
+        "c" => "c",
+        "b" => "b",
+        "B" => "B",
+        "h" => "h",
+        "H" => "H",
+        "i" => "i",
+        "I" => "I",
+        "l" => "l",
+        "L" => "L",
+        "q" => "q",
+        "Q" => "Q",
+        "f" => "f",
+        "d" => "d",
+        "g" => "d",
+        "u" => "i",
+        "?" => "?",
+        "P" | _ => "P",
+    }
+}
+
+/// Reads a native-endian scalar out of `bytes` (at least `size_of_type(ty)` long) and converts
+/// it to the matching Python value. Shared by `Structure`/`Union` field access and pointer
+/// dereferencing, which both just need "the bytes at this offset, interpreted as this type".
+pub fn bytes_to_pyobj(bytes: &[u8], ty: &str, vm: &VirtualMachine) -> PyObjectRef {
+    macro_rules! read_as {
+        ($t:ty) => {{
+            let mut buf = [0u8; std::mem::size_of::<$t>()];
+            buf.copy_from_slice(&bytes[..std::mem::size_of::<$t>()]);
+            <$t>::from_ne_bytes(buf)
+        }};
+    }
+
+    match ty {
+        "c" | "b" => vm.new_pyobj(read_as!(i8)),
+        "B" | "?" => vm.new_pyobj(read_as!(u8)),
+        "h" => vm.new_pyobj(read_as!(i16)),
+        "H" => vm.new_pyobj(read_as!(u16)),
+        "i" | "u" => vm.new_pyobj(read_as!(i32)),
+        "I" => vm.new_pyobj(read_as!(u32)),
+        "l" | "q" => vm.new_pyobj(read_as!(i64)),
+        "L" | "Q" => vm.new_pyobj(read_as!(u64)),
+        "f" => vm.new_pyobj(read_as!(f32)),
+        "d" | "g" => vm.new_pyobj(read_as!(f64)),
+        _ => vm.new_pyobj(read_as!(u64)),
+    }
+}
+
+/// Inverse of `bytes_to_pyobj`: converts `value` and writes its native-endian representation
+/// into `bytes` (at least `size_of_type(ty)` long).
+pub fn pyobj_to_bytes(bytes: &mut [u8], ty: &str, value: PyObjectRef, vm: &VirtualMachine) -> PyResult<()> {
+    macro_rules! write_as {
+        ($t:ty, $conv:expr) => {{
+            let v: $t = $conv;
+            bytes[..std::mem::size_of::<$t>()].copy_from_slice(&v.to_ne_bytes());
+        }};
+    }
+
+    match ty {
+        "c" | "b" => write_as!(i8, i8::try_from_object(vm, value)?),
+        "B" | "?" => write_as!(u8, u8::try_from_object(vm, value)?),
+        "h" => write_as!(i16, i16::try_from_object(vm, value)?),
+        "H" => write_as!(u16, u16::try_from_object(vm, value)?),
+        "i" | "u" => write_as!(i32, i32::try_from_object(vm, value)?),
+        "I" => write_as!(u32, u32::try_from_object(vm, value)?),
+        "l" | "q" => write_as!(i64, i64::try_from_object(vm, value)?),
+        "L" | "Q" => write_as!(u64, u64::try_from_object(vm, value)?),
+        "f" => write_as!(f32, f64::try_from_object(vm, value)? as f32),
+        "d" | "g" => write_as!(f64, f64::try_from_object(vm, value)?),
+        _ => write_as!(u64, u64::try_from_object(vm, value)?),
+    }
+    Ok(())
+}
+
+/// Builds the `middle::Type::structure` libffi needs to pass/return a `Structure`/`Union` by
+/// value, from the `_type_` codes of its fields in declaration order (as computed by
+/// `structure::compute_layout`).
+pub fn middle_struct_type(field_type_codes: &[String]) -> middle::Type {
+    middle::Type::structure(field_type_codes.iter().map(|s| convert_type(s.as_str())))
+}
+
 pub fn lib_call(
     c_args: Vec<middle::Type>,
     restype: middle::Type,
@@ -1,5 +1,6 @@
 extern crate libffi;
 
+use std::cell::{Cell, RefCell};
 use std::{fmt, os::raw::*, ptr};
 
 use crossbeam_utils::atomic::AtomicCell;
@@ -9,6 +10,8 @@ use libffi::low::{
     Error as FFIError,
 };
 use libffi::middle;
+use num_traits::ToPrimitive;
+use rustpython_common::borrow::BorrowValue;
 
 use crate::builtins::pystr::PyStrRef;
 use crate::builtins::{PyInt, PyTypeRef};
@@ -16,11 +19,15 @@ use crate::common::lock::PyRwLock;
 
 use crate::function::FuncArgs;
 use crate::pyobject::{
-    PyObjectRc, PyObjectRef, PyRef, PyResult, PyValue, StaticType, TryFromObject, TypeProtocol,
+    PyBaseExceptionRef, PyObjectRc, PyObjectRef, PyRef, PyResult, PyValue, StaticType,
+    TryFromObject, TypeProtocol,
 };
+use crate::vm::thread::with_vm;
 use crate::VirtualMachine;
 
 use crate::stdlib::ctypes::basics::PyCData;
+use crate::stdlib::ctypes::common::convert_type;
+use crate::stdlib::ctypes::pointer::{pointer_from_address, PyCByRef, PyCPointer};
 use crate::stdlib::ctypes::primitive::PySimpleType;
 
 use crate::slots::Callable;
@@ -90,6 +97,29 @@ pub fn str_to_type(ty: &str) -> *mut ffi_type {
     )
 }
 
+/// Converts `obj` (a Python `int`, arbitrary precision) to a signed 64-bit value, raising
+/// `OverflowError` instead of silently truncating when it doesn't fit. `c_longlong` is always
+/// 64 bits regardless of platform, so it is routed through this explicit check rather than
+/// the native-width `TryFromObject` conversion `c_long` uses.
+fn big_to_i64(obj: PyObjectRef, vm: &VirtualMachine) -> PyResult<i64> {
+    let int = obj
+        .downcast::<PyInt>()
+        .map_err(|o| vm.new_type_error(format!("an integer is required (got type {})", o.class().name)))?;
+    int.borrow_value()
+        .to_i64()
+        .ok_or_else(|| vm.new_overflow_error("int too large to convert".to_string()))
+}
+
+/// Unsigned counterpart of `big_to_i64`, used for `c_ulonglong`.
+fn big_to_u64(obj: PyObjectRef, vm: &VirtualMachine) -> PyResult<u64> {
+    let int = obj
+        .downcast::<PyInt>()
+        .map_err(|o| vm.new_type_error(format!("an integer is required (got type {})", o.class().name)))?;
+    int.borrow_value()
+        .to_u64()
+        .ok_or_else(|| vm.new_overflow_error("int too large to convert".to_string()))
+}
+
 fn py_to_ffi(
     ty: *mut *mut ffi_type,
     obj: PyObjectRef,
@@ -112,12 +142,24 @@ fn py_to_ffi(
         c_uint => {
             &mut u32::try_from_object(vm, obj)? as *mut _ as *mut c_void
         }
-        //@ TODO: Convert c*longlong from BigInt?
-        c_long | c_longlong => {
-            &mut i64::try_from_object(vm, obj)? as *mut _ as *mut c_void
+        //@ TODO: Structure/Union-by-value args and return values aren't marshalled here yet;
+        // `common::middle_struct_type` builds the libffi aggregate type but `match_ffi_type!`
+        // has no arm for it, so passing a Structure through `Function::call` still panics.
+        // `c_long` is native-width (32 bits on e.g. Windows/32-bit platforms, 64 elsewhere), so
+        // it goes through the ordinary sized conversion; `c_longlong` is always 64 bits and is
+        // routed through the explicit BigInt check so out-of-range values raise `OverflowError`
+        // instead of wrapping.
+        c_long => {
+            &mut c_long::try_from_object(vm, obj)? as *mut _ as *mut c_void
+        }
+        c_longlong => {
+            &mut big_to_i64(obj, vm)? as *mut _ as *mut c_void
         }
-        c_ulong | c_ulonglong => {
-            &mut u64::try_from_object(vm, obj)? as *mut _ as *mut c_void
+        c_ulong => {
+            &mut c_ulong::try_from_object(vm, obj)? as *mut _ as *mut c_void
+        }
+        c_ulonglong => {
+            &mut big_to_u64(obj, vm)? as *mut _ as *mut c_void
         }
         f32 => {
             &mut f32::try_from_object(vm, obj)? as *mut _ as *mut c_void
@@ -139,6 +181,270 @@ fn py_to_ffi(
     Ok(res)
 }
 
+/// Reads a single argument slot out of the raw `*mut c_void` array libffi hands a closure
+/// trampoline, converting it back into a `PyObjectRef`. This is the inverse of `py_to_ffi`,
+/// keyed off the same `ffi_type` match.
+fn ffi_to_py(ty: *mut *mut ffi_type, ptr: *mut c_void, vm: &VirtualMachine) -> PyObjectRef {
+    unsafe {
+        match_ffi_type!(
+            *ty,
+            c_schar => vm.new_pyobj(*(ptr as *mut i8))
+            c_int => vm.new_pyobj(*(ptr as *mut i32))
+            c_short => vm.new_pyobj(*(ptr as *mut i16))
+            c_ushort => vm.new_pyobj(*(ptr as *mut u16))
+            c_uint => vm.new_pyobj(*(ptr as *mut u32))
+            c_long => vm.new_pyobj(*(ptr as *mut c_long))
+            c_longlong => vm.new_pyobj(*(ptr as *mut i64))
+            c_ulong => vm.new_pyobj(*(ptr as *mut c_ulong))
+            c_ulonglong => vm.new_pyobj(*(ptr as *mut u64))
+            f32 => vm.new_pyobj(*(ptr as *mut f32))
+            f64 | longdouble => vm.new_pyobj(*(ptr as *mut f64))
+            c_uchar => vm.new_pyobj(*(ptr as *mut u8))
+            pointer => vm.new_pyobj(*(ptr as *mut usize))
+            void => vm.ctx.none()
+        )
+    }
+}
+
+/// Everything a closure trampoline needs once libffi calls it back: the Python callable to
+/// invoke, and the argument/return `ffi_type`s used to marshal values across the boundary.
+/// Boxed and kept alive by the `PyCClosure` that owns it (see the keep-alive model note on
+/// `PyCData` in basics.rs).
+struct ClosureUserData {
+    callable: PyObjectRc,
+    argtypes: Vec<*mut ffi_type>,
+    restype: *mut ffi_type,
+}
+
+unsafe impl Send for ClosureUserData {}
+unsafe impl Sync for ClosureUserData {}
+
+thread_local! {
+    // A Python exception raised by a callback invoked through `closure_callback` can't be
+    // re-raised from inside the trampoline -- there is no Python frame above it to unwind into,
+    // and unwinding across the FFI boundary that called it would be unsound. It's stashed here
+    // instead, and `PyCFuncPtr::call` re-raises it once control is back on the Python side of
+    // whatever native call triggered the callback.
+    static PENDING_CALLBACK_EXCEPTION: RefCell<Option<PyBaseExceptionRef>> = RefCell::new(None);
+}
+
+/// Takes and clears the exception (if any) stashed by a callback invoked since the last call,
+/// for `PyCFuncPtr::call` to re-raise after the native call that may have triggered it returns.
+fn take_pending_callback_exception() -> Option<PyBaseExceptionRef> {
+    PENDING_CALLBACK_EXCEPTION.with(|cell| cell.borrow_mut().take())
+}
+
+/// Trampoline invoked by libffi from native code. Marshals the raw argument array back into
+/// `PyObjectRef`s, calls the stored Python callable, and writes the converted return value into
+/// `result`. Must never unwind across the FFI boundary: a Python exception is stashed in
+/// `PENDING_CALLBACK_EXCEPTION` for `PyCFuncPtr::call` to re-raise, and the whole call is wrapped
+/// in `catch_unwind` so a panic inside the callback (or the marshalling around it) can't unwind
+/// through the C call stack that invoked this trampoline either -- both degrade to the zeroed
+/// `result` set below.
+extern "C" fn closure_callback(
+    _cif: &middle::Cif,
+    result: &mut u64,
+    args: *const *const c_void,
+    userdata: &ClosureUserData,
+) {
+    *result = 0;
+
+    let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        with_vm(&userdata.callable, |vm| -> PyResult<u64> {
+            let py_args: Vec<PyObjectRef> = userdata
+                .argtypes
+                .iter()
+                .enumerate()
+                .map(|(i, ty)| {
+                    let slot = unsafe { *args.add(i) } as *mut c_void;
+                    ffi_to_py(ty as *const _ as *mut *mut ffi_type, slot, vm)
+                })
+                .collect();
+
+            let ret = vm.invoke(&userdata.callable, py_args)?;
+
+            if userdata.restype == middle::Type::void().as_raw_ptr() {
+                return Ok(0);
+            }
+
+            match_ffi_type!(
+                userdata.restype,
+                c_schar | c_int | c_short | c_long => {
+                    i64::try_from_object(vm, ret)? as u64
+                }
+                c_longlong => {
+                    big_to_i64(ret, vm)? as u64
+                }
+                c_ushort | c_uint | c_ulong | c_uchar | pointer => {
+                    u64::try_from_object(vm, ret)?
+                }
+                c_ulonglong => {
+                    big_to_u64(ret, vm)?
+                }
+                f32 | f64 | longdouble => {
+                    f64::try_from_object(vm, ret)?.to_bits()
+                }
+                void => 0
+            )
+            .pipe(Ok)
+        })
+    }));
+
+    match outcome {
+        // Happy path: the callback ran and returned a value to hand back to native code.
+        Ok(Some(Ok(value))) => *result = value,
+        // The callback raised. Stash it for `PyCFuncPtr::call` to re-raise once this trampoline
+        // returns to the native call that triggered it and that call returns control to Python.
+        Ok(Some(Err(exc))) => {
+            PENDING_CALLBACK_EXCEPTION.with(|cell| *cell.borrow_mut() = Some(exc));
+        }
+        // A missing VM (callable's owning interpreter already torn down) or a panic inside the
+        // callback: there is no Python frame left to report anything to, so the zeroed sentinel
+        // set above is the best this trampoline can do.
+        Ok(None) | Err(_) => {}
+    }
+}
+
+trait Pipe: Sized {
+    fn pipe<R>(self, f: impl FnOnce(Self) -> R) -> R {
+        f(self)
+    }
+}
+impl<T> Pipe for T {}
+
+/// Owns the libffi `Closure` (and its `Cif` and boxed userdata) that backs a Python-callable
+/// `CFUNCTYPE` instance. All three must outlive every native call through `func_ptr`, so they
+/// live together in this struct rather than on the stack; `closure` borrows `userdata` through a
+/// lifetime extended to `'static` for that reason, which is sound only because `userdata` is
+/// never moved or dropped out from under it (it is owned by this same struct and declared after
+/// `closure`, so it is dropped after it).
+pub struct PyCClosure {
+    closure: middle::Closure<'static>,
+    _userdata: Box<ClosureUserData>,
+}
+
+impl fmt::Debug for PyCClosure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PyCClosure {{ .. }}")
+    }
+}
+
+unsafe impl Send for PyCClosure {}
+unsafe impl Sync for PyCClosure {}
+
+impl PyCClosure {
+    pub fn new(callable: PyObjectRc, arguments: Vec<String>, restype: &str) -> Self {
+        let argtypes: Vec<*mut ffi_type> =
+            arguments.iter().map(|s| str_to_type(s.as_str())).collect();
+        let restype_ptr = str_to_type(restype);
+
+        let userdata = Box::new(ClosureUserData {
+            callable,
+            argtypes: argtypes.clone(),
+            restype: restype_ptr,
+        });
+
+        let cif = middle::Cif::new(
+            arguments.iter().map(|s| convert_type(s.as_str())),
+            convert_type(restype),
+        );
+
+        let userdata_ref: &'static ClosureUserData =
+            unsafe { &*(userdata.as_ref() as *const ClosureUserData) };
+
+        let closure = middle::Closure::new(cif, closure_callback, userdata_ref);
+
+        PyCClosure {
+            _userdata: userdata,
+            closure,
+        }
+    }
+
+    /// The address native code should be handed to invoke this closure (e.g. as a `qsort`
+    /// comparator or a GUI callback). Usable anywhere a `pointer` argument is accepted by
+    /// `Function::call`.
+    pub fn func_ptr(&self) -> usize {
+        self.closure.code_ptr().as_ptr() as usize
+    }
+}
+
+thread_local! {
+    // The errno/GetLastError value observed after the most recent `use_errno`/`use_last_error`
+    // call on this thread, as `ctypes.get_errno()`/`get_last_error()` report it. Per-thread to
+    // match the OS values they shadow.
+    static LAST_ERRNO: Cell<i32> = Cell::new(0);
+    static LAST_WINERROR: Cell<u32> = Cell::new(0);
+}
+
+#[cfg(unix)]
+fn swap_errno(new: i32) -> i32 {
+    unsafe {
+        let loc = libc::__errno_location();
+        let old = *loc;
+        *loc = new;
+        old
+    }
+}
+
+#[cfg(windows)]
+fn swap_errno(new: i32) -> i32 {
+    unsafe {
+        let mut old: i32 = 0;
+        libc::_get_errno(&mut old);
+        libc::_set_errno(new);
+        old
+    }
+}
+
+#[cfg(windows)]
+extern "system" {
+    fn GetLastError() -> u32;
+    fn SetLastError(code: u32);
+}
+
+#[cfg(windows)]
+fn swap_last_error(new: u32) -> u32 {
+    unsafe {
+        let old = GetLastError();
+        SetLastError(new);
+        old
+    }
+}
+
+#[cfg(not(windows))]
+fn swap_last_error(_new: u32) -> u32 {
+    0
+}
+
+/// `ctypes.get_errno()`: the C `errno` left behind by the most recent call through a
+/// `use_errno=True` `CFuncPtr`, on this thread.
+#[pyfunction]
+pub fn get_errno() -> i32 {
+    LAST_ERRNO.with(Cell::get)
+}
+
+/// `ctypes.set_errno(value)`: overrides the saved `errno` that the next `use_errno=True` call
+/// will swap in, returning the previous saved value.
+#[pyfunction]
+pub fn set_errno(value: i32) -> i32 {
+    LAST_ERRNO.with(|cell| cell.replace(value))
+}
+
+/// `ctypes.get_last_error()`: the Windows `GetLastError()` value left behind by the most recent
+/// call through a `use_last_error=True` `CFuncPtr`, on this thread. Off Windows there is no
+/// `GetLastError` to read, so such a call stores `0`; otherwise this is what `set_last_error`
+/// last stored.
+#[pyfunction]
+pub fn get_last_error() -> u32 {
+    LAST_WINERROR.with(Cell::get)
+}
+
+/// `ctypes.set_last_error(value)`: as `set_errno`, for the `GetLastError` slot.
+#[pyfunction]
+pub fn set_last_error(value: u32) -> u32 {
+    LAST_WINERROR.with(|cell| cell.replace(value))
+}
+
 #[derive(Debug)]
 pub struct Function {
     pointer: *mut c_void,
@@ -224,14 +530,22 @@ impl Function {
                     let r: c_uint = ffi_call(cif_ptr, fun_ptr, arg_pointers.as_mut_ptr());
                     vm.new_pyobj(r as u32)
                 }
-                c_long | c_longlong => {
+                c_long => {
                     let r: c_long = ffi_call(cif_ptr, fun_ptr, arg_pointers.as_mut_ptr());
                     vm.new_pyobj(r as i64)
                 }
-                c_ulong | c_ulonglong => {
+                c_longlong => {
+                    let r: c_longlong = ffi_call(cif_ptr, fun_ptr, arg_pointers.as_mut_ptr());
+                    vm.new_pyobj(r)
+                }
+                c_ulong => {
                     let r: c_ulong = ffi_call(cif_ptr, fun_ptr, arg_pointers.as_mut_ptr());
                     vm.new_pyobj(r as u64)
                 }
+                c_ulonglong => {
+                    let r: c_ulonglong = ffi_call(cif_ptr, fun_ptr, arg_pointers.as_mut_ptr());
+                    vm.new_pyobj(r)
+                }
                 f32 => {
                     let r: c_float = ffi_call(cif_ptr, fun_ptr, arg_pointers.as_mut_ptr());
                     vm.new_pyobj(r as f32)
@@ -268,6 +582,14 @@ pub struct PyCFuncPtr {
     pub _restype_: AtomicCell<PyObjectRef>,
     _handle: PyObjectRc,
     _f: PyRwLock<Function>,
+    // Only set for instances created through CFUNCTYPE(...)(callable): keeps the libffi
+    // Closure (and everything it borrows) alive for as long as this object is.
+    _closure: Option<PyCClosure>,
+    // Settable at construction (`use_errno=True`/`use_last_error=True`, mirroring real ctypes'
+    // `CDLL`/`CFUNCTYPE` kwargs): whether `Callable::call` should swap the thread's errno/
+    // GetLastError around the native call so `get_errno()`/`get_last_error()` can see it.
+    use_errno: AtomicCell<bool>,
+    use_last_error: AtomicCell<bool>,
 }
 
 impl fmt::Debug for PyCFuncPtr {
@@ -295,6 +617,26 @@ impl PyCFuncPtr {
         unsafe { &*self._restype_.as_ptr() }.clone()
     }
 
+    #[pyproperty(name = "use_errno")]
+    fn use_errno(&self, _vm: &VirtualMachine) -> bool {
+        self.use_errno.load()
+    }
+
+    #[pyproperty(name = "use_errno", setter)]
+    fn set_use_errno(&self, value: bool, _vm: &VirtualMachine) {
+        self.use_errno.store(value);
+    }
+
+    #[pyproperty(name = "use_last_error")]
+    fn use_last_error(&self, _vm: &VirtualMachine) -> bool {
+        self.use_last_error.load()
+    }
+
+    #[pyproperty(name = "use_last_error", setter)]
+    fn set_use_last_error(&self, value: bool, _vm: &VirtualMachine) {
+        self.use_last_error.store(value);
+    }
+
     #[pyproperty(name = "_argtypes_", setter)]
     fn set_argtypes(&self, argtypes: PyObjectRef, vm: &VirtualMachine) -> PyResult<()> {
         if vm.isinstance(&argtypes, &vm.ctx.types.list_type).is_ok()
@@ -305,15 +647,28 @@ impl PyCFuncPtr {
                 .iter()
                 .enumerate()
                 .map(|(idx, inner_obj)| {
-                    match vm.isinstance(inner_obj, PySimpleType::static_type()) {
-                        // @TODO: checks related to _type_ are temporary
-                        // it needs to check for from_param method, instead
-                        Ok(_) => Ok(vm.get_attribute(inner_obj.clone(), "_type_").unwrap()),
-                        _ => Err(vm.new_type_error(format!(
+                    // @TODO: checks related to _type_ are temporary
+                    // it needs to check for from_param method, instead
+                    let is_pointer_type = match PyTypeRef::try_from_object(vm, inner_obj.clone()) {
+                        Ok(t) => vm.issubclass(&t, &PyCPointer::static_type())?,
+                        Err(_) => false,
+                    };
+                    if !is_pointer_type
+                        && vm.isinstance(inner_obj, PySimpleType::static_type()).is_ok()
+                    {
+                        Ok(vm.get_attribute(inner_obj.clone(), "_type_").unwrap())
+                    } else if is_pointer_type {
+                        // A `POINTER(...)` argtype has no scalar `_type_` code of its own;
+                        // `"Z"` is the letter `str_to_type`/`py_to_ffi` already resolve to the
+                        // libffi `pointer` type (the same one `z`/`Z` string-pointer codes use),
+                        // so it marshals as a raw address slot like any other native pointer.
+                        Ok(vm.ctx.new_str("Z".to_owned()))
+                    } else {
+                        Err(vm.new_type_error(format!(
                             "item {} in _argtypes_ must be subclass of _SimpleType, but type {} found",
                             idx,
                             inner_obj.class().name
-                        ))),
+                        )))
                     }
                 })
                 .collect();
@@ -341,9 +696,13 @@ impl PyCFuncPtr {
 
     #[pyproperty(name = "_restype_", setter)]
     fn set_restype(&self, restype: PyObjectRef, vm: &VirtualMachine) -> PyResult<()> {
-        match vm.isinstance(&restype, PySimpleType::static_type()) {
+        let is_pointer_type = match PyTypeRef::try_from_object(vm, restype.clone()) {
+            Ok(t) => vm.issubclass(&t, &PyCPointer::static_type())?,
+            Err(_) => false,
+        };
+        if !is_pointer_type && vm.isinstance(&restype, PySimpleType::static_type()).is_ok() {
             // @TODO: checks related to _type_ are temporary
-            Ok(_) => match vm.get_attribute(restype.clone(), "_type_") {
+            match vm.get_attribute(restype.clone(), "_type_") {
                 Ok(_type_) => {
                     // @TODO: restype must be a type, a callable, or None
                     self._restype_.store(restype.clone());
@@ -353,28 +712,119 @@ impl PyCFuncPtr {
                     Ok(())
                 }
                 Err(_) => Err(vm.new_attribute_error("atribute _type_ not found".to_string())),
-            },
+            }
+        } else if is_pointer_type {
+            // A `POINTER(...)` restype has no scalar `_type_` code of its own; `"Z"` is the
+            // pseudo-letter `str_to_type` resolves to the libffi `pointer` type, the same one
+            // pointer argtypes above are marshalled as. `Callable::call` wraps the raw address
+            // this produces back into a typed `_Pointer` instance of `restype`.
+            self._restype_.store(restype);
+            let mut fn_ptr = self._f.write();
+            fn_ptr.set_ret("Z");
 
-            Err(_) => Err(vm.new_type_error(format!(
+            Ok(())
+        } else {
+            Err(vm.new_type_error(format!(
                 "value is not an instance of _CData, type {} found",
                 restype.class().name
-            ))),
+            )))
         }
     }
 
+    /// `use_errno`/`use_last_error` are accepted as keyword arguments at construction, the way
+    /// real ctypes accepts them on `CDLL`/`CFUNCTYPE`.
+    fn errno_flags(args: &FuncArgs, vm: &VirtualMachine) -> PyResult<(bool, bool)> {
+        let use_errno = match args.kwargs.get("use_errno") {
+            Some(v) => bool::try_from_object(vm, v.clone())?,
+            None => false,
+        };
+        let use_last_error = match args.kwargs.get("use_last_error") {
+            Some(v) => bool::try_from_object(vm, v.clone())?,
+            None => false,
+        };
+        Ok((use_errno, use_last_error))
+    }
+
     // @TODO: Needs to check and implement other forms of new
     #[pyslot]
-    fn tp_new(
+    fn tp_new(cls: PyTypeRef, args: FuncArgs, vm: &VirtualMachine) -> PyResult<PyRef<Self>> {
+        let (use_errno, use_last_error) = Self::errno_flags(&args, vm)?;
+
+        match args.args.as_slice() {
+            [func_name, dll] => {
+                let func_name = PyStrRef::try_from_object(vm, func_name.clone())?;
+                match vm.get_attribute(cls.as_object().to_owned(), "_argtypes_") {
+                    Ok(_) => {
+                        Self::from_dll(cls, func_name, dll.clone(), use_errno, use_last_error, vm)
+                    }
+                    Err(_) => Err(vm.new_type_error(
+                        "cannot construct instance of this class: no argtypes slot".to_string(),
+                    )),
+                }
+            }
+            [callable] if vm.is_callable(callable) => {
+                Self::from_callable(cls, callable.clone(), use_errno, use_last_error, vm)
+            }
+            _ => Err(vm.new_type_error(
+                "argument must be a callable, or a (name, dll) pair".to_string(),
+            )),
+        }
+    }
+
+    /// Builds a `PyCFuncPtr` that wraps `callable` in a native trampoline, for use as the
+    /// result of calling a `CFUNCTYPE(restype, *argtypes)` type on a Python callable. `cls`
+    /// carries the `_argtypes_`/`_restype_` class attributes CFUNCTYPE attached.
+    fn from_callable(
         cls: PyTypeRef,
-        func_name: PyStrRef,
-        arg: PyObjectRef,
+        callable: PyObjectRef,
+        use_errno: bool,
+        use_last_error: bool,
         vm: &VirtualMachine,
     ) -> PyResult<PyRef<Self>> {
-        match vm.get_attribute(cls.as_object().to_owned(), "_argtypes_") {
-            Ok(_) => Self::from_dll(cls, func_name, arg, vm),
-            Err(_) => Err(vm.new_type_error(
-                "cannot construct instance of this class: no argtypes slot".to_string(),
-            )),
+        let argtypes: Vec<PyObjectRef> =
+            vm.extract_elements(&vm.get_attribute(cls.as_object().to_owned(), "_argtypes_")?)?;
+        let restype = vm.get_attribute(cls.as_object().to_owned(), "_restype_")?;
+
+        let arg_letters: PyResult<Vec<String>> = argtypes
+            .iter()
+            .map(|t| {
+                let type_ = vm.get_attribute(t.clone(), "_type_")?;
+                Ok(vm.to_str(&type_)?.to_string())
+            })
+            .collect();
+        let arg_letters = arg_letters?;
+
+        let ret_letter = if vm.is_none(&restype) {
+            "P".to_string()
+        } else {
+            vm.to_str(&vm.get_attribute(restype.clone(), "_type_")?)?
+                .to_string()
+        };
+
+        let closure = PyCClosure::new(callable.clone().into(), arg_letters.clone(), &ret_letter);
+        let fn_ptr = closure.func_ptr();
+
+        PyCFuncPtr {
+            _name_: "<callback>".to_string(),
+            _argtypes_: AtomicCell::new(argtypes),
+            _restype_: AtomicCell::new(restype),
+            _handle: callable.into(),
+            _f: PyRwLock::new(Function::new(fn_ptr, arg_letters, &ret_letter)),
+            _closure: Some(closure),
+            use_errno: AtomicCell::new(use_errno),
+            use_last_error: AtomicCell::new(use_last_error),
+        }
+        .into_ref_with_type(vm, cls)
+    }
+
+    /// The address of this function pointer, i.e. what `dlsym`/`CFUNCTYPE`'s trampoline
+    /// resolves to natively. Usable as a `pointer` argument to another `PyCFuncPtr.__call__`.
+    #[pyproperty(name = "func_ptr")]
+    fn func_ptr(&self, _vm: &VirtualMachine) -> usize {
+        if let Some(closure) = &self._closure {
+            closure.func_ptr()
+        } else {
+            self._f.read().pointer as usize
         }
     }
 
@@ -388,6 +838,8 @@ impl PyCFuncPtr {
         cls: PyTypeRef,
         func_name: PyStrRef,
         arg: PyObjectRef,
+        use_errno: bool,
+        use_last_error: bool,
         vm: &VirtualMachine,
     ) -> PyResult<PyRef<Self>> {
         if let Ok(h) = vm.get_attribute(arg.clone(), "_handle") {
@@ -406,6 +858,9 @@ impl PyCFuncPtr {
                         Vec::new(),
                         "i", // put a default here
                     )),
+                    _closure: None,
+                    use_errno: AtomicCell::new(use_errno),
+                    use_last_error: AtomicCell::new(use_last_error),
                 }
                 .into_ref_with_type(vm, cls)
             } else {
@@ -440,18 +895,89 @@ impl Callable for PyCFuncPtr {
                 if vm
                     .issubclass(&obj.clone_class(), PySimpleType::static_type())
                     .is_ok()
+                    || vm
+                        .issubclass(&obj.clone_class(), &PyCPointer::static_type())
+                        .is_ok()
                 {
+                    // Both `_SimpleType` instances and `_Pointer` instances expose their raw
+                    // marshalled value (the scalar, or the pointer's address) through the same
+                    // `value` property.
                     Ok(vm.get_attribute(obj.clone(), "value")?)
+                } else if let Ok(byref) = obj.clone().downcast::<PyCByRef>() {
+                    // `byref(x)`: a bare address marker, not a typed `_CData` -- feed its
+                    // address straight into the pointer slot libffi expects.
+                    Ok(vm.new_pyobj(byref.address))
                 } else {
                     Err(vm.new_type_error(format!(
-                        "positional argument {} must be subclass of _SimpleType, but type {} found",
+                        "positional argument {} must be subclass of _SimpleType, a pointer, or byref(), but type {} found",
                         idx,
                         obj.class().name
                     )))
                 }
             })
             .collect();
+        let arg_res = arg_res?;
+
+        let saved_errno = zelf
+            .use_errno
+            .load()
+            .then(|| swap_errno(LAST_ERRNO.with(Cell::get)));
+        let saved_last_error = zelf
+            .use_last_error
+            .load()
+            .then(|| swap_last_error(LAST_WINERROR.with(Cell::get)));
+
+        let result = (*zelf._f.write()).call(arg_res, vm);
+
+        if let Some(saved) = saved_errno {
+            let observed = swap_errno(saved);
+            LAST_ERRNO.with(|cell| cell.set(observed));
+        }
+        if let Some(saved) = saved_last_error {
+            let observed = swap_last_error(saved);
+            LAST_WINERROR.with(|cell| cell.set(observed));
+        }
+
+        // If this call passed through a `CFUNCTYPE` closure (directly, or via a native callback
+        // that invoked it), surface whatever exception it raised now that control is back on
+        // the Python side, rather than letting it disappear into the zeroed sentinel the
+        // trampoline wrote back to native code.
+        if let Some(exc) = take_pending_callback_exception() {
+            return Err(exc);
+        }
 
-        (*zelf._f.write()).call(arg_res?, vm)
+        // A `POINTER(...)` restype only gets as far as a raw address from `Function::call`
+        // (marshalled through the same libffi `pointer` type as any other pointer slot); wrap it
+        // into a real, dereferenceable `_Pointer` instance of that restype before handing it
+        // back to Python.
+        let restype = unsafe { &*zelf._restype_.as_ptr() }.clone();
+        if let Ok(restype_cls) = PyTypeRef::try_from_object(vm, restype) {
+            if vm.issubclass(&restype_cls, &PyCPointer::static_type())? {
+                let address = usize::try_from_object(vm, result?)?;
+                return pointer_from_address(restype_cls, address, vm).map(|p| p.into_object());
+            }
+        }
+
+        result
     }
 }
+
+/// `CFUNCTYPE(restype, *argtypes)`: builds a `CFuncPtr` subtype carrying `_restype_`/
+/// `_argtypes_` class attributes, mirroring what `from_dll` relies on for the `(name, dll)`
+/// construction path. Calling the resulting type on a Python callable (instead of a dll)
+/// routes through `PyCFuncPtr::from_callable`, producing a real native function pointer that
+/// wraps the callable via a libffi closure.
+pub fn cfunctype(args: FuncArgs, vm: &VirtualMachine) -> PyResult<PyTypeRef> {
+    let mut pos = args.args.into_iter();
+    let restype = pos.next().unwrap_or_else(|| vm.ctx.none());
+    let argtypes: Vec<PyObjectRef> = pos.collect();
+
+    let new_type = vm
+        .ctx
+        .new_class("CFunctionType", PyCFuncPtr::static_type(), Default::default());
+
+    vm.set_attr(new_type.as_object(), "_restype_", restype)?;
+    vm.set_attr(new_type.as_object(), "_argtypes_", vm.ctx.new_tuple(argtypes))?;
+
+    Ok(new_type)
+}
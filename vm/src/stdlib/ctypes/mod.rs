@@ -2,16 +2,22 @@ use crate::pyobject::PyClassImpl;
 use crate::pyobject::PyObjectRef;
 use crate::VirtualMachine;
 
+mod array;
 mod basics;
 mod common;
 mod dll;
 mod function;
+mod pointer;
 mod primitive;
+mod structure;
 
+use crate::stdlib::ctypes::array::*;
 use crate::stdlib::ctypes::basics::*;
 use crate::stdlib::ctypes::dll::*;
 use crate::stdlib::ctypes::function::*;
+use crate::stdlib::ctypes::pointer::*;
 use crate::stdlib::ctypes::primitive::*;
+use crate::stdlib::ctypes::structure::*;
 
 pub(crate) fn make_module(vm: &VirtualMachine) -> PyObjectRef {
     let ctx = &vm.ctx;
@@ -21,7 +27,23 @@ pub(crate) fn make_module(vm: &VirtualMachine) -> PyObjectRef {
         "dlsym" => ctx.new_function(dlsym),
 
         "CFuncPtr" => PyCFuncPtr::make_class(ctx),
+        "CFUNCTYPE" => ctx.new_function(cfunctype),
         "_CData" => PyCData::make_class(ctx),
-        "_SimpleCData" => PySimpleType::make_class(ctx)
+        "_SimpleCData" => PySimpleType::make_class(ctx),
+        "Structure" => PyCStructure::make_class(ctx),
+        "Union" => PyCUnion::make_class(ctx),
+        "Array" => PyCArray::make_class(ctx),
+
+        "_Pointer" => PyCPointer::make_class(ctx),
+        "CArgObject" => PyCByRef::make_class(ctx),
+        "POINTER" => ctx.new_function(pointer_type),
+        "pointer" => ctx.new_function(pointer),
+        "byref" => ctx.new_function(byref),
+        "addressof" => ctx.new_function(addressof),
+
+        "get_errno" => ctx.new_function(get_errno),
+        "set_errno" => ctx.new_function(set_errno),
+        "get_last_error" => ctx.new_function(get_last_error),
+        "set_last_error" => ctx.new_function(set_last_error)
     })
 }
@@ -0,0 +1,213 @@
+use std::fmt;
+
+use crossbeam_utils::atomic::AtomicCell;
+
+use crate::builtins::memory::{try_buffer_from_object, BufferOptions};
+use crate::builtins::{PyStr, PyTypeRef};
+use crate::function::FuncArgs;
+use crate::pyobject::{PyObjectRef, PyRef, PyResult, PyValue, StaticType, TypeProtocol};
+use crate::VirtualMachine;
+
+use crate::stdlib::ctypes::basics::{PyCData, PyCDataSequenceMethods};
+use crate::stdlib::ctypes::common::{bytes_to_pyobj, size_of_type};
+use crate::stdlib::ctypes::structure::{build_field_view, field_kind, type_info, FieldKind};
+
+/// The address of whatever buffer backs `obj` (the start of a `_CData`'s storage, as exposed
+/// through the buffer protocol). This is what `addressof`/`pointer`/`byref` all hand to native
+/// code or store for later dereferencing.
+pub fn address_of(obj: &PyObjectRef, vm: &VirtualMachine) -> PyResult<usize> {
+    let buffer = try_buffer_from_object(vm, obj)?;
+    Ok(buffer.obj_bytes().as_ptr() as usize)
+}
+
+#[pyfunction]
+pub fn addressof(obj: PyObjectRef, vm: &VirtualMachine) -> PyResult<usize> {
+    address_of(&obj, vm)
+}
+
+/// `POINTER(ctype)`: builds a `PyCPointer` subtype whose `_type_` class attribute is `ctype`,
+/// the same way `CFUNCTYPE` attaches `_restype_`/`_argtypes_` to a fresh `CFuncPtr` subtype.
+#[pyfunction]
+pub fn pointer_type(ctype: PyObjectRef, vm: &VirtualMachine) -> PyResult<PyTypeRef> {
+    let name = format!(
+        "LP_{}",
+        vm.get_attribute(ctype.clone(), "__name__")
+            .ok()
+            .and_then(|n| n.downcast_exact::<PyStr>(vm).ok())
+            .map(|n| n.to_string())
+            .unwrap_or_else(|| "_unknown_".to_string())
+    );
+
+    let new_type = vm
+        .ctx
+        .new_class(Box::leak(name.into_boxed_str()), PyCPointer::static_type(), Default::default());
+    vm.set_attr(new_type.as_object(), "_type_", ctype)?;
+
+    Ok(new_type)
+}
+
+/// `pointer(obj)`: a real `POINTER(type(obj))` instance whose address is `obj`'s storage. `obj`
+/// is kept alive via `target` (see the keep-alive model note on `PyCData` in basics.rs).
+#[pyfunction]
+pub fn pointer(obj: PyObjectRef, vm: &VirtualMachine) -> PyResult<PyRef<PyCPointer>> {
+    let address = address_of(&obj, vm)?;
+    let cls = pointer_type(obj.clone_class().into_object(), vm)?;
+
+    PyCPointer {
+        address: AtomicCell::new(address),
+        inner_type: obj.clone_class().into_object(),
+        target: AtomicCell::new(Some(obj)),
+    }
+    .into_ref_with_type(vm, cls)
+}
+
+/// Builds a `_Pointer` instance of `cls` directly from a raw address, with no target object to
+/// keep alive -- used for a `CFuncPtr` whose `restype` is a `POINTER(...)` type, where all that
+/// comes back across the FFI boundary is the address itself.
+pub(crate) fn pointer_from_address(
+    cls: PyTypeRef,
+    address: usize,
+    vm: &VirtualMachine,
+) -> PyResult<PyRef<PyCPointer>> {
+    let inner_type = vm.get_attribute(cls.as_object().to_owned(), "_type_")?;
+    PyCPointer {
+        address: AtomicCell::new(address),
+        inner_type,
+        target: AtomicCell::new(None),
+    }
+    .into_ref_with_type(vm, cls)
+}
+
+/// `byref(obj)`: a lightweight address marker, cheaper than `pointer(obj)` because it doesn't
+/// build a typed `_CData` instance -- it is only ever consumed by argument marshalling
+/// (`Function::call`/`PyCFuncPtr.__call__`), which reads `.address` straight off it
+/// instead of going through the buffer protocol again.
+#[pyclass(module = "_ctypes", name = "CArgObject")]
+#[derive(Debug)]
+pub struct PyCByRef {
+    pub address: usize,
+    // Keep-alive for `obj` (see the keep-alive model note on `PyCData` in basics.rs).
+    pub target: PyObjectRef,
+}
+
+impl PyValue for PyCByRef {
+    fn class(_vm: &VirtualMachine) -> &crate::builtins::PyTypeRef {
+        Self::static_type()
+    }
+}
+
+#[pyimpl]
+impl PyCByRef {}
+
+#[pyfunction]
+pub fn byref(obj: PyObjectRef, vm: &VirtualMachine) -> PyResult<PyCByRef> {
+    let address = address_of(&obj, vm)?;
+    Ok(PyCByRef {
+        address,
+        target: obj,
+    })
+}
+
+#[pyclass(module = "_ctypes", name = "_Pointer", base = "PyCData")]
+pub struct PyCPointer {
+    address: AtomicCell<usize>,
+    inner_type: PyObjectRef,
+    // Keep-alive for whatever `pointer()`/`.contents =` last pointed this at (see the keep-alive
+    // model note on `PyCData` in basics.rs).
+    target: AtomicCell<Option<PyObjectRef>>,
+}
+
+impl fmt::Debug for PyCPointer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "_Pointer({:#x})", self.address.load())
+    }
+}
+
+impl PyValue for PyCPointer {
+    fn class(_vm: &VirtualMachine) -> &PyTypeRef {
+        Self::static_type()
+    }
+}
+
+impl PyCDataSequenceMethods for PyCPointer {}
+
+#[pyimpl(with(PyCDataSequenceMethods), flags(BASETYPE))]
+impl PyCPointer {
+    #[pyslot]
+    fn tp_new(cls: PyTypeRef, args: FuncArgs, vm: &VirtualMachine) -> PyResult<PyRef<Self>> {
+        let inner_type = vm.get_attribute(cls.as_object().to_owned(), "_type_")?;
+        let target = args.args.into_iter().next();
+        let address = match &target {
+            Some(obj) => address_of(obj, vm)?,
+            None => 0,
+        };
+
+        PyCPointer {
+            address: AtomicCell::new(address),
+            inner_type,
+            target: AtomicCell::new(target),
+        }
+        .into_ref_with_type(vm, cls)
+    }
+
+    #[pyproperty(name = "contents")]
+    fn contents(&self, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+        let address = self.address.load();
+        if address == 0 {
+            return Err(vm.new_value_error("NULL pointer access".to_string()));
+        }
+
+        match field_kind(&self.inner_type, vm)? {
+            FieldKind::Scalar(code) => {
+                // `PySimpleType` (`primitive.rs`) has no `PyCData`-backed buffer of its own to
+                // alias -- unlike `Array`/`Structure` below, it still stores its value as a
+                // plain Rust field, not a `Sub`/`External` view -- so this can only copy the
+                // pointee's bytes into a fresh instance. `p.contents.value = 5` does not write
+                // back through `p`'s address; giving `PySimpleType` the same root/view storage
+                // the aggregate types already have would be required to close that gap.
+                let size = size_of_type(code.as_str());
+                let bytes = unsafe { std::slice::from_raw_parts(address as *const u8, size) };
+                let value = bytes_to_pyobj(bytes, code.as_str(), vm);
+                vm.invoke(&self.inner_type, vec![value])
+            }
+            FieldKind::Aggregate => {
+                // `Array`/`Structure`/`Union` pointees alias the pointed-to memory directly:
+                // the "root" here is the raw address itself, the same kind of `External`
+                // storage `from_buffer` builds over a Python buffer object, just without a
+                // buffer-protocol object backing it. `p.contents.field = 5` writes straight
+                // back through `p`'s address because there is no copy in between.
+                let (size, _align) = type_info(&self.inner_type, vm)?;
+                let options = BufferOptions {
+                    len: size,
+                    itemsize: 1,
+                    readonly: false,
+                    format: "B".into(),
+                    ..Default::default()
+                };
+                let root =
+                    PyCData::new_external_unowned(address as *mut u8, size, options).into_ref(vm);
+                build_field_view(self.inner_type.clone(), root, 0, size, vm)
+            }
+        }
+    }
+
+    #[pyproperty(name = "contents", setter)]
+    fn set_contents(&self, value: PyObjectRef, vm: &VirtualMachine) -> PyResult<()> {
+        let address = address_of(&value, vm)?;
+        self.address.store(address);
+        // `PyCPointer` doesn't wrap a `PyCData`-backed buffer the way `PyCArray` does, so it has
+        // no `_objects` table of its own to register `value` in; `target` is this type's
+        // keep-alive slot instead, serving the same role for the one pointee a `_Pointer` can
+        // ever hold at a time.
+        self.target.store(Some(value));
+        Ok(())
+    }
+
+    #[pyproperty(name = "value")]
+    fn value(&self, vm: &VirtualMachine) -> PyObjectRef {
+        vm.new_pyobj(self.address.load())
+    }
+}
+
+unsafe impl Send for PyCPointer {}
+unsafe impl Sync for PyCPointer {}
@@ -0,0 +1,694 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::builtins::memory::{try_buffer_from_object, Buffer, BufferOptions};
+use crate::builtins::pystr::PyStrRef;
+use crate::builtins::{PyStr, PyTypeRef};
+use crate::common::borrow::{BorrowedValue, BorrowedValueMut};
+use crate::common::lock::PyRwLock;
+use crate::pyobject::{
+    PyObjectRef, PyRef, PyResult, PyValue, StaticType, TryFromObject, TypeProtocol,
+};
+use crate::VirtualMachine;
+
+use crate::stdlib::ctypes::array::{array_from_view, PyCArray};
+use crate::stdlib::ctypes::basics::{PyCData, PyCDataSequenceMethods};
+use crate::stdlib::ctypes::common::{align_of_type, bytes_to_pyobj, pyobj_to_bytes, size_of_type};
+use crate::stdlib::ctypes::pointer::PyCPointer;
+
+/// The `PyCData` root/view backing `value`, if it's one of the aggregate ctypes (`Array`,
+/// `Structure`, `Union`) -- so a nested-field assignment can fold the source's own keep-alive
+/// table into the field it was just copied into (`StructureData::setattr`'s `Aggregate` arm).
+fn pycdata_of(value: &PyObjectRef) -> Option<PyRef<PyCData>> {
+    if let Ok(s) = value.clone().downcast::<PyCStructure>() {
+        return Some(s.data.buffer.clone());
+    }
+    if let Ok(u) = value.clone().downcast::<PyCUnion>() {
+        return Some(u.data.buffer.clone());
+    }
+    if let Ok(a) = value.clone().downcast::<PyCArray>() {
+        return Some(a.buffer.clone());
+    }
+    None
+}
+
+/// A field declared with a bit width (`("flags", c_uint, 3)` in `_fields_`): its position within
+/// the storage unit at `FieldDesc::offset`, rather than being the whole unit.
+#[derive(Debug, Clone, Copy)]
+pub struct BitField {
+    pub bit_offset: usize,
+    pub bit_size: usize,
+    pub signed: bool,
+}
+
+/// One resolved `_fields_` entry: its name, its byte offset within the structure/union, its
+/// size, and the ctype object it was declared with (read for its `_type_` code on scalar fields,
+/// and used to build the aliasing view of an aggregate field). `bitfield` is `Some`
+/// when the field only occupies part of the `size`-byte storage unit at `offset`.
+#[derive(Debug, Clone)]
+pub struct FieldDesc {
+    pub name: String,
+    pub offset: usize,
+    pub size: usize,
+    pub ctype: PyObjectRef,
+    pub bitfield: Option<BitField>,
+}
+
+/// Whether `code` (a `_type_` single-character code) denotes a signed integer type, for
+/// sign-extending bitfield reads.
+fn is_signed_type(code: &str) -> bool {
+    matches!(code, "b" | "h" | "i" | "l" | "q")
+}
+
+/// Reads bit `bit_index` (counting from the start of `buffer`) as a bool.
+fn get_bit(buffer: &[u8], bit_index: usize) -> bool {
+    let byte = buffer[bit_index / 8];
+    let shift = bit_index % 8;
+    (byte >> shift) & 1 == 1
+}
+
+/// Sets bit `bit_index` (counting from the start of `buffer`) to `value`.
+fn set_bit(buffer: &mut [u8], bit_index: usize, value: bool) {
+    let byte = &mut buffer[bit_index / 8];
+    let shift = bit_index % 8;
+    if value {
+        *byte |= 1 << shift;
+    } else {
+        *byte &= !(1 << shift);
+    }
+}
+
+/// Loads a `bit_size`-bit field starting at bit `byte_offset * 8 + bit_offset`, sign-extending
+/// when `signed` is set.
+fn read_bitfield(buffer: &[u8], byte_offset: usize, bit_offset: usize, bit_size: usize, signed: bool) -> u64 {
+    let base = byte_offset * 8 + bit_offset;
+    let mut value: u64 = 0;
+    for i in 0..bit_size {
+        if get_bit(buffer, base + i) {
+            value |= 1 << i;
+        }
+    }
+    if signed && bit_size < 64 && bit_size > 0 && value & (1 << (bit_size - 1)) != 0 {
+        value |= !0u64 << bit_size;
+    }
+    value
+}
+
+/// Masks `value` to its low `bit_size` bits and stores it at bit `byte_offset * 8 + bit_offset`,
+/// leaving the rest of the storage unit untouched.
+fn write_bitfield(buffer: &mut [u8], byte_offset: usize, bit_offset: usize, bit_size: usize, value: u64) {
+    let base = byte_offset * 8 + bit_offset;
+    for i in 0..bit_size {
+        set_bit(buffer, base + i, (value >> i) & 1 == 1);
+    }
+}
+
+/// `(size, alignment)` of a ctype object used as a `_fields_` entry: a `_SimpleCData` subclass
+/// (by its `_type_` code), an `Array` (by its element type and `_length_`), or a nested
+/// `Structure`/`Union` (by laying out its own `_fields_` with `compute_layout`).
+pub(crate) fn type_info(ty_obj: &PyObjectRef, vm: &VirtualMachine) -> PyResult<(usize, usize)> {
+    // A `POINTER(X)` class's own `_type_` is the pointee *class* `X`, not a single-character
+    // string -- checked first, the same way `array.rs`'s `make_array_with_lenght` checks this
+    // before assuming `_type_` is a string, so `POINTER(X)` can be used as a `_fields_` entry.
+    if let Ok(cls) = PyTypeRef::try_from_object(vm, ty_obj.clone()) {
+        if vm.issubclass(&cls, &PyCPointer::static_type())? {
+            return Ok((size_of_type("P"), align_of_type("P")));
+        }
+    }
+
+    if let Ok(type_code) = vm.get_attribute(ty_obj.clone(), "_type_") {
+        if let Ok(length) = vm.get_attribute(ty_obj.clone(), "_length_") {
+            // Array(inner_type, length): size is the element size times the length, alignment
+            // is the element's.
+            let length = usize::try_from_object(vm, length)?;
+            let (elem_size, elem_align) = type_info(&type_code, vm)?;
+            return Ok((elem_size * length, elem_align));
+        }
+
+        let code = type_code
+            .downcast_exact::<PyStr>(vm)
+            .map_err(|_| vm.new_type_error("_type_ must be a single character string".to_string()))?;
+        return Ok((size_of_type(code.as_str()), align_of_type(code.as_str())));
+    }
+
+    if vm.get_attribute(ty_obj.clone(), "_fields_").is_ok() {
+        let cls = PyTypeRef::try_from_object(vm, ty_obj.clone())?;
+        let is_union = vm.issubclass(&cls, &PyCUnion::static_type())?;
+        let (_fields, size, align) = compute_layout(&cls, is_union, vm)?;
+        return Ok((size, align));
+    }
+
+    if let (Ok(size), Ok(align)) = (
+        vm.get_attribute(ty_obj.clone(), "_size_"),
+        vm.get_attribute(ty_obj.clone(), "_align_"),
+    ) {
+        return Ok((
+            usize::try_from_object(vm, size)?,
+            usize::try_from_object(vm, align)?,
+        ));
+    }
+
+    Err(vm.new_type_error(format!(
+        "{} is not a valid ctypes field type",
+        ty_obj.clone_class().name
+    )))
+}
+
+fn round_up(offset: usize, align: usize) -> usize {
+    if align == 0 {
+        offset
+    } else {
+        (offset + align - 1) / align * align
+    }
+}
+
+/// The `_type_` single-character code of a scalar ctype, used to tell whether two bitfields
+/// share the same underlying storage unit.
+fn type_code_of(ty_obj: &PyObjectRef, vm: &VirtualMachine) -> PyResult<String> {
+    let type_code = vm.get_attribute(ty_obj.clone(), "_type_").map_err(|_| {
+        vm.new_type_error("bit fields not allowed for this type".to_string())
+    })?;
+    let code = type_code
+        .downcast_exact::<PyStr>(vm)
+        .map_err(|_| vm.new_type_error("_type_ must be a single character string".to_string()))?;
+    Ok(code.to_string())
+}
+
+/// Walks `_fields_` (a list/tuple of `(name, ctype)` or `(name, ctype, bitwidth)` entries,
+/// `_pack_` honored as a cap on per-field alignment) and computes each field's
+/// `(offset, size)` -- plus `(bit_offset, bit_size)` for bitfields -- and the whole aggregate's
+/// total size and alignment. `is_union` lays every field at offset 0 instead of sequentially.
+///
+/// Bitfields are packed into "storage units" of `sizeof(underlying type)` bytes: consecutive
+/// fields of the same underlying type share a unit while bits remain, a declared width of 0
+/// forces the next field into a fresh unit, and a unit is also started fresh whenever the
+/// underlying type changes or the current one runs out of room.
+pub fn compute_layout(
+    cls: &PyTypeRef,
+    is_union: bool,
+    vm: &VirtualMachine,
+) -> PyResult<(Vec<FieldDesc>, usize, usize)> {
+    let fields_obj = vm.get_attribute(cls.as_object().to_owned(), "_fields_")?;
+    let raw_fields: Vec<PyObjectRef> = vm.extract_elements(&fields_obj)?;
+
+    let pack = match vm.get_attribute(cls.as_object().to_owned(), "_pack_") {
+        Ok(p) => Some(usize::try_from_object(vm, p)?),
+        Err(_) => None,
+    };
+
+    let mut fields = Vec::with_capacity(raw_fields.len());
+    let mut cursor = 0usize;
+    let mut max_align = 1usize;
+
+    // Bitfield storage-unit state (structures only; union bitfields are independent, each
+    // sitting at offset 0).
+    let mut unit_code: Option<String> = None;
+    let mut unit_offset = 0usize;
+    let mut unit_size = 0usize;
+    let mut bit_cursor = 0usize;
+
+    for entry in raw_fields {
+        let tuple: Vec<PyObjectRef> = vm.extract_elements(&entry)?;
+        if tuple.len() != 2 && tuple.len() != 3 {
+            return Err(vm.new_type_error(
+                "_fields_ entries must be (name, ctype) or (name, ctype, width) tuples"
+                    .to_string(),
+            ));
+        }
+        let name = tuple[0]
+            .clone()
+            .downcast_exact::<PyStr>(vm)
+            .map_err(|_| vm.new_type_error("_fields_ field name must be a string".to_string()))?;
+        let ctype = tuple[1].clone();
+        let width = match tuple.get(2) {
+            Some(w) => Some(usize::try_from_object(vm, w.clone())?),
+            None => None,
+        };
+
+        let (size, mut align) = type_info(&ctype, vm)?;
+        if let Some(pack) = pack {
+            align = align.min(pack.max(1));
+        }
+        max_align = max_align.max(align);
+
+        let width = match width {
+            None => {
+                // An ordinary field closes whatever bit storage unit was open.
+                unit_code = None;
+                None
+            }
+            Some(width) => {
+                let code = type_code_of(&ctype, vm)?;
+                let max_bits = size * 8;
+                if width > max_bits {
+                    return Err(vm.new_value_error(format!(
+                        "bit width ({}) exceeds width of its type ({})",
+                        width, max_bits
+                    )));
+                }
+                Some((code, width))
+            }
+        };
+
+        let (offset, bitfield) = match width {
+            None => {
+                let offset = if is_union {
+                    0
+                } else {
+                    let offset = round_up(cursor, align);
+                    cursor = offset + size;
+                    offset
+                };
+                (offset, None)
+            }
+            Some((_, 0)) => {
+                // Width 0 is a pure alignment token: it forces the next field into a new
+                // storage unit and never produces a field of its own. Closing the open unit
+                // here (as an ordinary field does above) is required so a same-type bitfield
+                // right after this one doesn't get packed back into the stale unit at its old
+                // offset instead of starting a fresh one.
+                if !is_union {
+                    cursor = round_up(cursor, align);
+                    unit_code = None;
+                    unit_offset = 0;
+                    unit_size = 0;
+                    bit_cursor = 0;
+                }
+                continue;
+            }
+            Some((code, width)) => {
+                let signed = is_signed_type(&code);
+                if is_union {
+                    // Union bitfields don't share a storage unit; each overlays byte 0.
+                    (0, Some(BitField { bit_offset: 0, bit_size: width, signed }))
+                } else if unit_code.as_deref() == Some(code.as_str())
+                    && bit_cursor + width <= unit_size * 8
+                {
+                    let bitfield = BitField { bit_offset: bit_cursor, bit_size: width, signed };
+                    bit_cursor += width;
+                    (unit_offset, Some(bitfield))
+                } else {
+                    unit_offset = round_up(cursor, align);
+                    unit_size = size;
+                    unit_code = Some(code);
+                    bit_cursor = width;
+                    cursor = unit_offset + size;
+                    (unit_offset, Some(BitField { bit_offset: 0, bit_size: width, signed }))
+                }
+            }
+        };
+
+        fields.push(FieldDesc {
+            name: name.to_string(),
+            offset,
+            size,
+            ctype,
+            bitfield,
+        });
+    }
+
+    let total_size = if is_union {
+        fields.iter().map(|f| f.size).max().unwrap_or(0)
+    } else {
+        round_up(cursor, max_align)
+    };
+
+    Ok((fields, total_size, max_align.max(1)))
+}
+
+/// Whether a `_fields_` entry's ctype is a scalar (single-character `_type_` code) or an
+/// aggregate (`Array`, or a nested `Structure`/`Union`, identified by `_length_`/`_fields_`
+/// respectively) -- aggregates round-trip through an aliasing view rather than `bytes_to_pyobj`.
+pub(crate) enum FieldKind {
+    Scalar(String),
+    Aggregate,
+}
+
+pub(crate) fn field_kind(ctype: &PyObjectRef, vm: &VirtualMachine) -> PyResult<FieldKind> {
+    // As in `type_info`, a `POINTER(X)`'s `_type_` is the pointee class, not a string -- ruled
+    // out before the `_type_` check below ever sees it. A pointer field is stored as its raw
+    // address, the same as any other scalar.
+    if let Ok(cls) = PyTypeRef::try_from_object(vm, ctype.clone()) {
+        if vm.issubclass(&cls, &PyCPointer::static_type())? {
+            return Ok(FieldKind::Scalar("P".to_string()));
+        }
+    }
+    // Checked before `_type_`: an `Array`'s `_type_` is its element ctype object, not a
+    // single-character code, so `_length_` has to be ruled out first.
+    if vm.get_attribute(ctype.clone(), "_length_").is_ok() {
+        return Ok(FieldKind::Aggregate);
+    }
+    if let Ok(type_code) = vm.get_attribute(ctype.clone(), "_type_") {
+        let code = type_code
+            .downcast_exact::<PyStr>(vm)
+            .map_err(|_| vm.new_type_error("_type_ must be a single character string".to_string()))?;
+        return Ok(FieldKind::Scalar(code.to_string()));
+    }
+    // No `_type_`/`_length_`: a nested `Structure`/`Union`.
+    Ok(FieldKind::Aggregate)
+}
+
+/// Builds the `_CData` instance an aggregate field (`Array`, nested `Structure`/`Union`)
+/// dereferences to, aliasing `[offset, offset + size)` of `root` rather than copying it, so
+/// `outer.inner.x = 5` writes back into `outer`'s own storage.
+pub(crate) fn build_field_view(
+    ctype: PyObjectRef,
+    root: PyRef<PyCData>,
+    offset: usize,
+    size: usize,
+    vm: &VirtualMachine,
+) -> PyResult<PyObjectRef> {
+    let cls = PyTypeRef::try_from_object(vm, ctype.clone())?;
+    if vm.issubclass(&cls, &PyCUnion::static_type())? {
+        PyCUnion::from_view(PyTypeRef::try_from_object(vm, ctype)?, root, offset, size, vm)
+    } else if vm.issubclass(&cls, &PyCStructure::static_type())? {
+        PyCStructure::from_view(PyTypeRef::try_from_object(vm, ctype)?, root, offset, size, vm)
+    } else {
+        array_from_view(PyTypeRef::try_from_object(vm, ctype)?, root, offset, size, vm)
+    }
+}
+
+/// Backing state shared by `Structure` and `Union` instances: the raw bytes (sized by layout),
+/// the resolved field table, and anything assigned that isn't one of `_fields_` (so plain
+/// instance attributes keep working alongside the synthesized field accessors).
+struct StructureData {
+    // A real `PyCData` root rather than an independent `Vec<u8>`, so a struct field that holds
+    // a pointer/array/nested-struct aliases this same storage (and shares its `BorrowFlag`)
+    // instead of bypassing the borrow-arbitration every other `_CData` subtype goes through.
+    buffer: PyRef<PyCData>,
+    fields: Vec<FieldDesc>,
+    extra: PyRwLock<HashMap<String, PyObjectRef>>,
+    // `_anonymous_`: names of `fields` (each itself a Structure/Union) whose own members are
+    // promoted onto this instance, e.g. `s.x` reaching into `s`'s anonymous `Structure` field
+    // instead of requiring `s.anon_field.x`.
+    anonymous: Vec<String>,
+}
+
+impl StructureData {
+    fn new(cls: &PyTypeRef, is_union: bool, vm: &VirtualMachine) -> PyResult<Self> {
+        let (fields, size, _align) = compute_layout(cls, is_union, vm)?;
+        let options = BufferOptions {
+            len: size,
+            itemsize: 1,
+            readonly: false,
+            format: "B".into(),
+            ..Default::default()
+        };
+        let buffer = PyCData::new_with_options(None, vec![0u8; size], options).into_ref(vm);
+        let anonymous = match vm.get_attribute(cls.as_object().to_owned(), "_anonymous_") {
+            Ok(names) => vm
+                .extract_elements::<PyStrRef>(&names)?
+                .into_iter()
+                .map(|s| s.to_string())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        Ok(StructureData {
+            buffer,
+            fields,
+            extra: PyRwLock::new(HashMap::new()),
+            anonymous,
+        })
+    }
+
+    fn field(&self, name: &str) -> Option<&FieldDesc> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    /// Looks `name` up among the direct fields of `_anonymous_`'s nested `Structure`/`Union`
+    /// fields and returns its absolute `(offset, size, ctype, bitfield)` within this instance's
+    /// own buffer. Only one level is searched: members that a nested field itself promotes
+    /// through its own `_anonymous_` are not found.
+    fn find_anonymous(&self, name: &str, vm: &VirtualMachine) -> PyResult<Option<FieldDesc>> {
+        for anon_name in &self.anonymous {
+            let anon_field = match self.field(anon_name) {
+                Some(f) => f,
+                None => continue,
+            };
+            let anon_cls = PyTypeRef::try_from_object(vm, anon_field.ctype.clone())?;
+            let is_union = vm.issubclass(&anon_cls, &PyCUnion::static_type())?;
+            let (nested_fields, _, _) = compute_layout(&anon_cls, is_union, vm)?;
+            if let Some(nested) = nested_fields.iter().find(|f| f.name == name) {
+                return Ok(Some(FieldDesc {
+                    name: nested.name.clone(),
+                    offset: anon_field.offset + nested.offset,
+                    size: nested.size,
+                    ctype: nested.ctype.clone(),
+                    bitfield: nested.bitfield,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Resolves `name` to a field, either one of `self.fields` directly or one reached through
+    /// `_anonymous_` promotion.
+    fn resolve(&self, name: &str, vm: &VirtualMachine) -> PyResult<Option<FieldDesc>> {
+        if let Some(field) = self.field(name) {
+            return Ok(Some(field.clone()));
+        }
+        self.find_anonymous(name, vm)
+    }
+
+    fn getattr(&self, name: &str, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+        if let Some(field) = self.resolve(name, vm)? {
+            let buffer = self.buffer.try_borrow(vm)?;
+
+            if let Some(bitfield) = field.bitfield {
+                let raw = read_bitfield(
+                    &buffer,
+                    field.offset,
+                    bitfield.bit_offset,
+                    bitfield.bit_size,
+                    bitfield.signed,
+                );
+                return Ok(if bitfield.signed {
+                    vm.new_pyobj(raw as i64)
+                } else {
+                    vm.new_pyobj(raw)
+                });
+            }
+
+            return match field_kind(&field.ctype, vm)? {
+                FieldKind::Scalar(code) => {
+                    let bytes = &buffer[field.offset..field.offset + field.size];
+                    Ok(bytes_to_pyobj(bytes, code.as_str(), vm))
+                }
+                FieldKind::Aggregate => {
+                    drop(buffer);
+                    build_field_view(field.ctype, self.buffer.clone(), field.offset, field.size, vm)
+                }
+            };
+        }
+
+        self.extra
+            .read()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| vm.new_attribute_error(name.to_string()))
+    }
+
+    fn setattr(&self, name: &str, value: PyObjectRef, vm: &VirtualMachine) -> PyResult<()> {
+        if let Some(field) = self.resolve(name, vm)? {
+            if let Some(bitfield) = field.bitfield {
+                let mut buffer = self.buffer.try_borrow_mut(vm)?;
+                let raw: u64 = if bitfield.signed {
+                    i64::try_from_object(vm, value)? as u64
+                } else {
+                    u64::try_from_object(vm, value)?
+                };
+                write_bitfield(
+                    &mut buffer,
+                    field.offset,
+                    bitfield.bit_offset,
+                    bitfield.bit_size,
+                    raw,
+                );
+                return Ok(());
+            }
+
+            return match field_kind(&field.ctype, vm)? {
+                FieldKind::Scalar(code) => {
+                    let mut buffer = self.buffer.try_borrow_mut(vm)?;
+                    let bytes = &mut buffer[field.offset..field.offset + field.size];
+                    pyobj_to_bytes(bytes, code.as_str(), value, vm)
+                }
+                FieldKind::Aggregate => {
+                    // Assigning an aggregate field copies the source's bytes in (CPython's
+                    // `Structure`/`Array` `__set__` semantics), rather than rebinding what this
+                    // field aliases -- that's what `s.point` (the `getattr` view above) is for.
+                    let source = try_buffer_from_object(vm, &value)?;
+                    let src_bytes = source.obj_bytes();
+                    if src_bytes.len() != field.size {
+                        return Err(vm.new_value_error(format!(
+                            "expected a buffer of size {}, got {}",
+                            field.size,
+                            src_bytes.len()
+                        )));
+                    }
+                    let mut buffer = self.buffer.try_borrow_mut(vm)?;
+                    buffer[field.offset..field.offset + field.size].copy_from_slice(&src_bytes);
+                    drop(buffer);
+                    drop(src_bytes);
+                    // The bytes were copied, but `value` may itself hold a pointer/nested-struct
+                    // field keeping something alive (e.g. `outer.inner = some_struct_with_a_ptr`)
+                    // -- fold its keep-alive table into this field's position so that survives
+                    // the copy too, the same way CPython merges a sub-object's `b_objects` into
+                    // its parent's.
+                    if let Some(child) = pycdata_of(&value) {
+                        self.buffer.merge_objects(field.offset, &child);
+                    }
+                    Ok(())
+                }
+            };
+        }
+
+        self.extra.write().insert(name.to_string(), value);
+        Ok(())
+    }
+}
+
+impl fmt::Debug for StructureData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "StructureData {{ fields: {} }}", self.fields.len())
+    }
+}
+
+macro_rules! structure_like {
+    ($name:ident, $py_name:literal, $is_union:literal) => {
+        #[pyclass(module = "_ctypes", name = $py_name, base = "PyCData")]
+        #[derive(Debug)]
+        pub struct $name {
+            data: StructureData,
+        }
+
+        impl PyValue for $name {
+            fn class(_vm: &VirtualMachine) -> &PyTypeRef {
+                Self::static_type()
+            }
+        }
+
+        impl PyCDataSequenceMethods for $name {}
+
+        // Delegates to the backing `PyCData` root/view, the same way `PyCArray` does -- this is
+        // what lets a nested-field copy-assignment (`StructureData::setattr`'s `Aggregate` arm)
+        // read a `Structure`/`Union` value's bytes through the ordinary buffer protocol.
+        impl Buffer for $name {
+            fn obj_bytes(&self) -> BorrowedValue<[u8]> {
+                self.data.buffer.obj_bytes()
+            }
+
+            fn obj_bytes_mut(&self) -> BorrowedValueMut<[u8]> {
+                self.data.buffer.obj_bytes_mut()
+            }
+
+            fn release(&self) {
+                self.data.buffer.release()
+            }
+
+            fn get_options(&self) -> BorrowedValue<BufferOptions> {
+                self.data.buffer.get_options()
+            }
+        }
+
+        #[pyimpl(with(PyCDataSequenceMethods, Buffer), flags(BASETYPE))]
+        impl $name {
+            #[pyslot]
+            fn tp_new(cls: PyTypeRef, vm: &VirtualMachine) -> PyResult<PyRef<Self>> {
+                let data = StructureData::new(&cls, $is_union, vm)?;
+                $name { data }.into_ref_with_type(vm, cls)
+            }
+
+            #[pymethod(magic)]
+            fn getattr(&self, name: PyStrRef, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+                self.data.getattr(name.as_str(), vm)
+            }
+
+            #[pymethod(magic)]
+            fn setattr(&self, name: PyStrRef, value: PyObjectRef, vm: &VirtualMachine) -> PyResult<()> {
+                self.data.setattr(name.as_str(), value, vm)
+            }
+        }
+
+        impl $name {
+            /// Builds a `$name` that aliases `[offset, offset + size)` of `root` instead of
+            /// allocating its own storage -- used for a nested `Structure`/`Union`-typed field
+            /// (`StructureData::getattr`'s `Aggregate` arm), so writes through the child view
+            /// land back in the parent's buffer.
+            pub(crate) fn from_view(
+                field_cls: PyTypeRef,
+                root: PyRef<PyCData>,
+                offset: usize,
+                size: usize,
+                vm: &VirtualMachine,
+            ) -> PyResult<PyObjectRef> {
+                let (fields, _size, _align) = compute_layout(&field_cls, $is_union, vm)?;
+                let options = BufferOptions {
+                    len: size,
+                    itemsize: 1,
+                    readonly: false,
+                    format: "B".into(),
+                    ..Default::default()
+                };
+                let view = PyCData::new_view(root, offset, size, options).into_ref(vm);
+                let anonymous = match vm.get_attribute(field_cls.as_object().to_owned(), "_anonymous_") {
+                    Ok(names) => vm
+                        .extract_elements::<PyStrRef>(&names)?
+                        .into_iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                    Err(_) => Vec::new(),
+                };
+                let data = StructureData {
+                    buffer: view,
+                    fields,
+                    extra: PyRwLock::new(HashMap::new()),
+                    anonymous,
+                };
+                Ok($name { data }.into_ref_with_type(vm, field_cls)?.into_object())
+            }
+        }
+    };
+}
+
+structure_like!(PyCStructure, "Structure", false);
+structure_like!(PyCUnion, "Union", true);
+
+#[cfg(test)]
+mod tests {
+    use super::{read_bitfield, round_up, write_bitfield};
+
+    #[test]
+    fn bitfield_round_trips_within_a_byte() {
+        let mut buffer = vec![0u8; 1];
+        write_bitfield(&mut buffer, 0, 2, 3, 0b101);
+        assert_eq!(read_bitfield(&buffer, 0, 2, 3, false), 0b101);
+        // Bits outside [2, 5) stay untouched.
+        assert_eq!(buffer[0] & 0b1110_0011, 0);
+    }
+
+    #[test]
+    fn bitfield_sign_extends() {
+        let mut buffer = vec![0u8; 1];
+        // A 3-bit field holding -1 (0b111).
+        write_bitfield(&mut buffer, 0, 0, 3, 0b111);
+        assert_eq!(read_bitfield(&buffer, 0, 0, 3, true) as i64, -1);
+        assert_eq!(read_bitfield(&buffer, 0, 0, 3, false), 0b111);
+    }
+
+    #[test]
+    fn bitfield_crosses_byte_boundary() {
+        let mut buffer = vec![0u8; 2];
+        write_bitfield(&mut buffer, 0, 6, 5, 0b10101);
+        assert_eq!(read_bitfield(&buffer, 0, 6, 5, false), 0b10101);
+    }
+
+    #[test]
+    fn round_up_aligns_to_the_next_multiple() {
+        assert_eq!(round_up(0, 4), 0);
+        assert_eq!(round_up(1, 4), 4);
+        assert_eq!(round_up(4, 4), 4);
+        assert_eq!(round_up(5, 8), 8);
+        assert_eq!(round_up(5, 0), 5);
+    }
+}